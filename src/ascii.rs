@@ -2,6 +2,11 @@ use bevy::prelude::*;
 
 pub const TILE_SIZE: f32 = 16.0;
 
+// The sprite sheet is a packed grid of 16px sprites, indexed left to right, top to bottom
+pub const SHEET_PATH: &str = "tilemap_packed.png";
+pub const SHEET_COLUMNS: usize = 12;
+pub const SHEET_ROWS: usize = 11;
+
 pub struct AsciiPlugin;
 
 #[derive(Resource)]
@@ -20,9 +25,15 @@ fn setup(
 ) {
     println!("Load sprite sheet...");
 
-    let texture_handle = asset_server.load("tilemap_packed.png");
-    let texture_atlas =
-        TextureAtlas::from_grid(texture_handle, Vec2::splat(TILE_SIZE), 12, 11, None, None);
+    let texture_handle = asset_server.load(SHEET_PATH);
+    let texture_atlas = TextureAtlas::from_grid(
+        texture_handle,
+        Vec2::splat(TILE_SIZE),
+        SHEET_COLUMNS,
+        SHEET_ROWS,
+        None,
+        None,
+    );
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
 
     commands.insert_resource(AsciiSheet(texture_atlas_handle));
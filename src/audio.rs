@@ -1,7 +1,11 @@
 use bevy::audio::PlaybackMode;
 use bevy::prelude::*;
 
+use crate::levels::Levels;
 use crate::movement::PlayerState;
+use crate::{GameState, Level};
+
+const DEFAULT_MUSIC: &str = "dungeon-level.ogg";
 
 pub struct AudioPlugin;
 
@@ -10,6 +14,7 @@ impl Plugin for AudioPlugin {
         app.init_resource::<AudioAssets>()
             .add_systems(Startup, (load_audio_assets, setup).chain())
             .add_systems(Update, pause)
+            .add_systems(OnEnter(GameState::GameSetup), play_level_music)
             .add_systems(OnEnter(PlayerState::Teleport), play_teleport_sfx)
             .add_systems(OnEnter(PlayerState::CollectPotion), play_collect_potion_sfx);
     }
@@ -24,7 +29,7 @@ pub struct AudioAssets {
 
 fn load_audio_assets(mut audio_assets: ResMut<AudioAssets>, asset_server: Res<AssetServer>) {
     *audio_assets = AudioAssets {
-        music: asset_server.load("dungeon-level.ogg"),
+        music: asset_server.load(DEFAULT_MUSIC),
         teleport: asset_server.load("teleport.ogg"),
         potion_collect: asset_server.load("potion-collect.ogg"),
     }
@@ -47,7 +52,42 @@ fn setup(audio_assets: Res<AudioAssets>, mut commands: Commands) {
                 ..default()
             },
         },
-        Music,
+        Music(DEFAULT_MUSIC.to_string()),
+    ));
+}
+
+// Switch to the level's music track (if it has one that isn't already playing)
+fn play_level_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Res<Level>,
+    levels: Levels,
+    music_query: Query<(Entity, &Music, Option<&AudioSink>)>,
+) {
+    let track = levels
+        .get(&level)
+        .and_then(|level_def| level_def.music.clone())
+        .unwrap_or(DEFAULT_MUSIC.to_string());
+
+    let mut paused = false;
+    for (entity, music, sink) in &music_query {
+        if music.0 == track {
+            return;
+        }
+        paused = sink.is_some_and(|sink| sink.is_paused());
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(&track),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                paused,
+                ..default()
+            },
+        },
+        Music(track),
     ));
 }
 
@@ -77,8 +117,9 @@ fn play_collect_potion_sfx(audio_assets: Res<AudioAssets>, mut commands: Command
     ));
 }
 
+// Looping background music, tagged with the track it is playing
 #[derive(Component, Debug)]
-pub struct Music;
+pub struct Music(String);
 
 #[derive(Component, Debug)]
 pub struct Sfx;
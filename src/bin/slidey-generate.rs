@@ -0,0 +1,187 @@
+// Generates random levels that the solver has checked can be finished, e.g
+//
+//   cargo run --bin slidey-generate -- --difficulty hard --count 5 --out generated
+//
+// Levels are written in the same format as the built in ones, with their par set to the length of
+// the shortest solution. Without `--out` they are printed, separated by blank lines.
+
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use slidey::generator::{generate, Difficulty, GeneratorOptions};
+use slidey::rng::Rng;
+
+const USAGE: &str = "Usage: slidey-generate [OPTION...]
+
+Generates random levels that can be solved in a number of moves within the given difficulty.
+
+Options:
+  --difficulty NAME  easy (6-15 moves), medium (16-30) or hard (31-60) [default: medium]
+  --moves A-B        Shortest solution length, overrides --difficulty
+  --branching A-B    Average moves to choose from at each step of the solution [default: 0-5]
+  --size WxH         Board size, including the outer wall [default: 12x9]
+  --potions A-B      Number of potions [default: 4-10]
+  --walls N          Chance of each tile being a wall, from 0 to 1 [default: 0.2]
+  --attempts N       Boards to try for each level before giving up [default: 2000]
+  --seed N           Seed for the random numbers, the same seed gives the same levels
+  --count N          Number of levels to generate [default: 1]
+  --out DIR          Write levels to DIR/level-NN.level instead of printing them";
+
+struct Options {
+    generator: GeneratorOptions,
+    seed: u64,
+    count: usize,
+    out: Option<PathBuf>,
+}
+
+// Parses `A-B`, or a lone `A` for a range of one
+fn parse_range<T: FromStr + Copy>(value: &str) -> Option<RangeInclusive<T>> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        generator: GeneratorOptions::default(),
+        seed: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+        count: 1,
+        out: None,
+    };
+    let mut moves = None;
+    let generator = &mut options.generator;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "--difficulty" => {
+                generator.difficulty = match value("--difficulty")?.as_str() {
+                    "easy" => Difficulty::EASY,
+                    "medium" => Difficulty::MEDIUM,
+                    "hard" => Difficulty::HARD,
+                    other => return Err(format!("unknown difficulty {other}")),
+                };
+            }
+            "--moves" => {
+                moves =
+                    Some(parse_range(&value("--moves")?).ok_or("--moves must look like 10-20")?);
+            }
+            "--branching" => {
+                generator.difficulty.branching = parse_range(&value("--branching")?)
+                    .ok_or("--branching must look like 2.5-4")?;
+            }
+            "--size" => {
+                let size = value("--size")?;
+                let (width, height) = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .ok_or("--size must look like 12x9")?;
+                generator.width = width;
+                generator.height = height;
+            }
+            "--potions" => {
+                generator.potions =
+                    parse_range(&value("--potions")?).ok_or("--potions must look like 4-10")?;
+            }
+            "--walls" => {
+                generator.wall_density = value("--walls")?
+                    .parse()
+                    .ok()
+                    .filter(|density| (0.0..=1.0).contains(density))
+                    .ok_or("--walls must be a number from 0 to 1")?;
+            }
+            "--attempts" => {
+                generator.attempts = value("--attempts")?
+                    .parse()
+                    .map_err(|_| "--attempts must be a whole number")?;
+            }
+            "--seed" => {
+                options.seed = value("--seed")?
+                    .parse()
+                    .map_err(|_| "--seed must be a whole number")?;
+            }
+            "--count" => {
+                options.count = value("--count")?
+                    .parse()
+                    .map_err(|_| "--count must be a whole number")?;
+            }
+            "--out" => options.out = Some(value("--out")?.into()),
+            _ => return Err(format!("unknown option {arg}")),
+        }
+    }
+
+    // Applied last so it wins over --difficulty whichever comes first
+    if let Some(moves) = moves {
+        generator.difficulty.moves = moves;
+    }
+
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(args.into_iter()) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(out) = &options.out {
+        if let Err(err) = std::fs::create_dir_all(out) {
+            eprintln!("{}: {err}", out.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    // Printed to stderr so it doesn't end up in piped levels
+    eprintln!("seed {}", options.seed);
+    let mut rng = Rng::new(options.seed);
+
+    for number in 1..=options.count {
+        let generated = match generate(&options.generator, &mut rng) {
+            Ok(generated) => generated,
+            Err(err) => {
+                eprintln!("error: {err}");
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let mut level = generated.level;
+        level.name = format!("Generated {number}");
+        level.author = Some("slidey-generate".into());
+
+        match &options.out {
+            Some(out) => {
+                let path = out.join(format!("level-{number:02}.level"));
+                if let Err(err) = std::fs::write(&path, level.to_string()) {
+                    eprintln!("{}: {err}", path.display());
+                    return ExitCode::FAILURE;
+                }
+                println!(
+                    "{} ({} moves, branching {:.2})",
+                    path.display(),
+                    generated.solution.len(),
+                    generated.branching
+                );
+            }
+            None => {
+                if number > 1 {
+                    println!();
+                }
+                print!("{level}");
+            }
+        }
+    }
+
+    ExitCode::SUCCESS
+}
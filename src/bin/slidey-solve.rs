@@ -0,0 +1,124 @@
+// Solves levels and prints the shortest solution for each, e.g
+//
+//   cargo run --bin slidey-solve -- assets/levels/original/level-01.level
+//
+// Moves are written U(p), D(own), L(eft), R(ight) and T(eleport). Exits with an error when any
+// level is invalid or can't be solved, so it can be used to check levels before committing them.
+// With no files given every built in pack is solved.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use slidey::level_files::{builtin_packs, read_levels, LevelFile};
+use slidey::sim::Board;
+use slidey::solver::{solve_with_limit, MAX_STATES};
+
+const USAGE: &str = "Usage: slidey-solve [--assets DIR] [--max-states N] [FILE...]
+
+Prints the shortest solution to every level in the given .pack and .level files, or of every built
+in pack when no files are given.
+
+Options:
+  --assets DIR      Folder holding the built in packs [default: assets]
+  --max-states N    Give up on a level after searching this many states [default: 5000000]";
+
+struct Options {
+    assets: PathBuf,
+    max_states: usize,
+    files: Vec<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        assets: PathBuf::from("assets"),
+        max_states: MAX_STATES,
+        files: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "--assets" => options.assets = value("--assets")?.into(),
+            "--max-states" => {
+                options.max_states = value("--max-states")?
+                    .parse()
+                    .map_err(|_| "--max-states must be a whole number")?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => options.files.push(arg.into()),
+        }
+    }
+
+    if options.files.is_empty() {
+        options.files = builtin_packs(&options.assets);
+    }
+
+    Ok(options)
+}
+
+// Prints the solution to a level, returning false when there isn't one
+fn report(file: &LevelFile, max_states: usize) -> bool {
+    let LevelFile { path, level } = file;
+    println!("{} ({})", path.display(), level.name);
+
+    match solve_with_limit(&Board::new(level), max_states) {
+        Ok(solution) => {
+            let moves: Vec<String> = solution.moves.iter().map(|mv| mv.to_string()).collect();
+            println!("  {}", moves.join(" "));
+            println!(
+                "  {} moves, {} states explored",
+                moves.len(),
+                solution.states_explored
+            );
+            if let Some(par) = level.par.filter(|par| *par as usize != moves.len()) {
+                println!(
+                    "  note: par is {par}, the shortest solution is {}",
+                    moves.len()
+                );
+            }
+            true
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", path.display());
+            false
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(args.into_iter()) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Keep going past broken levels, so one doesn't hide problems with the rest
+    let mut failed = false;
+    for path in &options.files {
+        match read_levels(path) {
+            Ok(files) => {
+                for file in &files {
+                    failed |= !report(file, options.max_states);
+                }
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
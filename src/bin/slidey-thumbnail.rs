@@ -0,0 +1,146 @@
+// Renders PNG thumbnails of levels, without a window or GPU, e.g
+//
+//   cargo run --bin slidey-thumbnail -- --scale 2 assets/levels/hard.pack
+//
+// Each level in a pack is written to `<out>/<pack>/<level>.png`, a lone level file to
+// `<out>/<level>.png`. With no files given every built in pack is rendered.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use image::RgbaImage;
+use slidey::ascii::SHEET_PATH;
+use slidey::level_def::LevelDef;
+use slidey::level_files::{builtin_packs, is_pack, read_levels, LevelFileError};
+use slidey::thumbnail;
+
+const USAGE: &str = "Usage: slidey-thumbnail [--assets DIR] [--out DIR] [--scale N] [FILE...]
+
+Renders a PNG thumbnail of every level in the given .pack and .level files, or of every built in
+pack when no files are given.
+
+Options:
+  --assets DIR  Folder holding the sprite sheet and built in packs [default: assets]
+  --out DIR     Folder to write thumbnails to [default: thumbnails]
+  --scale N     Size of each 16px tile, in multiples [default: 1]";
+
+struct Options {
+    assets: PathBuf,
+    out: PathBuf,
+    scale: u32,
+    files: Vec<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        assets: PathBuf::from("assets"),
+        out: PathBuf::from("thumbnails"),
+        scale: 1,
+        files: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{name} needs a value"));
+        match arg.as_str() {
+            "--assets" => options.assets = value("--assets")?.into(),
+            "--out" => options.out = value("--out")?.into(),
+            "--scale" => {
+                options.scale = value("--scale")?
+                    .parse()
+                    .ok()
+                    .filter(|scale| *scale > 0)
+                    .ok_or("--scale must be a whole number above 0")?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => options.files.push(arg.into()),
+        }
+    }
+
+    if options.files.is_empty() {
+        options.files = builtin_packs(&options.assets);
+    }
+
+    Ok(options)
+}
+
+// The levels to render from a file, with the path each thumbnail should be written to
+fn levels_in(path: &Path, out: &Path) -> Result<Vec<(LevelDef, PathBuf)>, LevelFileError> {
+    let stem = |path: &Path| path.file_stem().unwrap_or_default().to_owned();
+    let out = if is_pack(path) {
+        out.join(stem(path))
+    } else {
+        out.to_path_buf()
+    };
+
+    Ok(read_levels(path)?
+        .into_iter()
+        .map(|file| {
+            let thumbnail_path = out.join(stem(&file.path)).with_extension("png");
+            (file.level, thumbnail_path)
+        })
+        .collect())
+}
+
+fn save(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    }
+    image
+        .save(path)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let sheet_path = options.assets.join(SHEET_PATH);
+    let sheet = image::open(&sheet_path)
+        .map_err(|err| format!("{}: {err}", sheet_path.display()))?
+        .to_rgba8();
+
+    // Keep going past broken files, so one bad level doesn't hide problems with the rest
+    let mut failed = false;
+    for file in &options.files {
+        let levels = match levels_in(file, &options.out) {
+            Ok(levels) => levels,
+            Err(err) => {
+                eprintln!("{err}");
+                failed = true;
+                continue;
+            }
+        };
+
+        for (level_def, path) in levels {
+            save(&thumbnail::render(&level_def, &sheet, options.scale), &path)?;
+            println!("{} -> {}", level_def.name, path.display());
+        }
+    }
+
+    if failed {
+        Err("some levels could not be rendered".into())
+    } else {
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let options = match parse_args(args.into_iter()) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
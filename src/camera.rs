@@ -1,12 +1,20 @@
 use bevy::{prelude::*, render::camera::ScalingMode};
 
-/// -- Camera
+use crate::{ascii::TILE_SIZE, map::MapSize};
+
+// Space kept around the board (in tiles) for the HUD text
+const BOARD_MARGIN: Vec2 = Vec2::new(4.0 * TILE_SIZE, 3.0 * TILE_SIZE);
+
+// -- Camera
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera);
+        app.add_systems(Startup, spawn_camera).add_systems(
+            Update,
+            fit_camera_to_map.run_if(resource_exists_and_changed::<MapSize>()),
+        );
     }
 }
 
@@ -22,3 +30,18 @@ fn spawn_camera(mut commands: Commands) {
 
     commands.spawn(camera);
 }
+
+// Zoom the camera so the whole board (plus margin) is visible, whatever the level dimensions
+fn fit_camera_to_map(
+    map_size: Res<MapSize>,
+    mut projection_query: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    let min_size = map_size.world_size() + BOARD_MARGIN;
+
+    for mut projection in &mut projection_query {
+        projection.scaling_mode = ScalingMode::AutoMin {
+            min_width: min_size.x,
+            min_height: min_size.y,
+        };
+    }
+}
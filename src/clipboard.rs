@@ -0,0 +1,28 @@
+// System clipboard access for sharing level codes. Browsers only allow clipboard access from
+// JavaScript, so on WASM these do nothing and players copy the code from the screen instead.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn copy(text: &str) -> bool {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|err| println!("Could not copy to clipboard: {err}"))
+        .is_ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn paste() -> Option<String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|err| println!("Could not paste from clipboard: {err}"))
+        .ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn copy(_text: &str) -> bool {
+    false
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn paste() -> Option<String> {
+    None
+}
@@ -0,0 +1,424 @@
+use std::fmt;
+use std::str::FromStr;
+
+use bevy::{prelude::*, utils::Instant};
+
+use crate::button::{button_style, button_text_style, NORMAL_BUTTON};
+use crate::clipboard;
+use crate::game::OnGameCompleted;
+use crate::generator::{Difficulty, GenerateError, Generated, Generator, GeneratorOptions};
+use crate::level_def::LevelDef;
+use crate::levels::{LevelAsset, LevelManager, LevelPack, Levels};
+use crate::menu::MenuButtonAction;
+use crate::movement::{GameTick, LevelStats};
+use crate::playback::SolutionShown;
+use crate::rng::Rng;
+use crate::{GameState, Level, FRAME_BUDGET};
+
+// The daily puzzle is a generated level seeded from the date, so everyone playing on the same day
+// gets the same board. Days follow UTC, so they change at the same moment for everyone. Like
+// endless levels it is generated a little each frame, so the game doesn't stall while it is made.
+//
+// The first result of each day is kept on the device, in a file on native builds and the
+// browser's local storage on the web, and can be shared as a short text summary.
+
+// Name of the results file in the data folder on native, and the local storage key on the web
+const RESULTS_NAME: &str = "daily-results.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    // The date a number of days after 1970-01-01, using Howard Hinnant's `civil_from_days`
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months counted from March, so the leap day comes last
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn today() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    // The standard library has no clock on the web, so ask the browser
+    #[cfg(target_arch = "wasm32")]
+    pub fn today() -> Self {
+        let now = js_sys::Date::new_0();
+        Self {
+            year: now.get_utc_full_year() as i32,
+            month: now.get_utc_month() + 1,
+            day: now.get_utc_date(),
+        }
+    }
+
+    // Reads as the date, e.g 20261018
+    pub fn seed(&self) -> u64 {
+        self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64
+    }
+}
+
+// Written as YYYY-MM-DD
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '-').map(|part| part.parse::<u32>().ok());
+        let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(());
+        };
+
+        Ok(Self {
+            year: year as i32,
+            month,
+            day,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DailyResult {
+    pub date: Date,
+    pub moves: u32,
+    pub par: u32,
+    pub seconds: u64,
+}
+
+impl DailyResult {
+    // Text for sharing with other players, it gives away how well it went but not how
+    pub fn summary(&self) -> String {
+        format!(
+            "Wizards Conundrum daily {}\nSolved in {} moves (par {}) in {}:{:02}",
+            self.date,
+            self.moves,
+            self.par,
+            self.seconds / 60,
+            self.seconds % 60
+        )
+    }
+}
+
+// Results are stored a line each, as `date moves par seconds`
+impl fmt::Display for DailyResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.date, self.moves, self.par, self.seconds
+        )
+    }
+}
+
+impl FromStr for DailyResult {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let mut next = || fields.next().ok_or(());
+
+        Ok(Self {
+            date: next()?.parse()?,
+            moves: next()?.parse().map_err(|_| ())?,
+            par: next()?.parse().map_err(|_| ())?,
+            seconds: next()?.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+// Results from earlier days, lines that can't be read are skipped
+pub fn parse_results(source: &str) -> Vec<DailyResult> {
+    source
+        .lines()
+        .filter_map(|line| line.parse().ok())
+        .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn results_path() -> Option<std::path::PathBuf> {
+    Some(dirs::data_dir()?.join("slidey").join(RESULTS_NAME))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_results() -> String {
+    results_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_results(source: &str) {
+    let Some(path) = results_path() else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, source));
+    if let Err(err) = written {
+        println!("Could not save daily results to {}: {err}", path.display());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_results() -> String {
+    local_storage()
+        .and_then(|storage| storage.get_item(RESULTS_NAME).ok()?)
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_results(source: &str) {
+    let written = local_storage().map(|storage| storage.set_item(RESULTS_NAME, source));
+    if !matches!(written, Some(Ok(()))) {
+        println!("Could not save daily results to local storage");
+    }
+}
+
+// Generates the puzzle for a day, the same for everyone
+pub fn generator(date: Date) -> Generator {
+    let options = GeneratorOptions {
+        difficulty: Difficulty::MEDIUM,
+        ..default()
+    };
+    Generator::new(options, Rng::new(date.seed()))
+}
+
+// The puzzle for a day, generated all at once
+pub fn level(date: Date) -> Option<LevelDef> {
+    name_level(date, generator(date).finish())
+}
+
+// Names the puzzle after its day, `None` if it couldn't be generated
+fn name_level(date: Date, result: Result<Generated, GenerateError>) -> Option<LevelDef> {
+    match result {
+        Ok(generated) => {
+            let mut level_def = generated.level;
+            level_def.name = format!("Daily {date}");
+            Some(level_def)
+        }
+        Err(err) => {
+            println!("Could not generate the daily puzzle for {date}: {err}");
+            None
+        }
+    }
+}
+
+// Present while the daily puzzle is being played
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct DailyPuzzle {
+    pub date: Date,
+}
+
+// The daily puzzle while it is being generated, it is played once it is ready
+#[derive(Resource, Debug)]
+pub struct PendingDaily {
+    date: Date,
+    generator: Generator,
+}
+
+impl PendingDaily {
+    pub fn new(date: Date) -> Self {
+        Self {
+            date,
+            generator: generator(date),
+        }
+    }
+}
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            generate_daily.run_if(resource_exists::<PendingDaily>()),
+        )
+        .add_systems(
+            OnEnter(GameState::GameCompleted),
+            daily_completed.run_if(resource_exists::<DailyPuzzle>()),
+        )
+        .add_systems(OnEnter(GameState::Menu), end_daily);
+    }
+}
+
+// Sets the puzzle up as a pack of its own once it has been generated
+fn generate_daily(
+    mut commands: Commands,
+    mut pending: ResMut<PendingDaily>,
+    mut level_assets: ResMut<Assets<LevelAsset>>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+) {
+    let started = Instant::now();
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(result) = pending.generator.step() else {
+            continue;
+        };
+
+        commands.remove_resource::<PendingDaily>();
+        let date = pending.date;
+        let Some(level_def) = name_level(date, result) else {
+            return;
+        };
+        let pack = LevelPack {
+            title: format!("Daily {date}"),
+            author: None,
+            levels: vec![level_assets.add(LevelAsset(level_def))],
+        };
+        level_manager.custom = Some(pack_assets.add(pack));
+        commands.insert_resource(DailyPuzzle { date });
+        return;
+    }
+}
+
+// Saves the result, when it is the first of the day, and shows it ready for sharing in place of
+// the usual end of game screen
+fn daily_completed(
+    mut commands: Commands,
+    daily: Res<DailyPuzzle>,
+    level_stats: Res<LevelStats>,
+    game_tick: Res<GameTick>,
+    solution_shown: Res<SolutionShown>,
+    level: Res<Level>,
+    levels: Levels,
+) {
+    let result = DailyResult {
+        date: daily.date,
+        moves: level_stats.moves,
+        par: levels
+            .get(&level)
+            .and_then(|level_def| level_def.par)
+            .unwrap_or(0),
+        seconds: game_tick.seconds(),
+    };
+
+    let mut results = parse_results(&read_results());
+    let first = results.iter().find(|earlier| earlier.date == result.date);
+    let (title, shared) = match first {
+        Some(first) => ("Solved again, your first result today stands", *first),
+        // Only results worked out by the player count
+        None if solution_shown.0 => ("Solution shown, play it yourself for a result", result),
+        None => {
+            results.push(result);
+            let source: String = results.iter().map(|result| format!("{result}\n")).collect();
+            write_results(&source);
+            ("Daily puzzle solved!", result)
+        }
+    };
+    println!("{title}: {result}");
+
+    let summary = shared.summary();
+    let copied = if clipboard::copy(&summary) {
+        "Copied to clipboard, share it with your friends"
+    } else {
+        "Share it with your friends"
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameCompleted,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (text, font_size, background) in [
+                        (title, 40.0, Color::NONE),
+                        (summary.as_str(), 20.0, Color::rgb(0.15, 0.15, 0.15)),
+                        (copied, 20.0, Color::NONE),
+                    ] {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                text,
+                                TextStyle {
+                                    font_size,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                padding: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            })
+                            .with_text_alignment(TextAlignment::Center)
+                            .with_background_color(background),
+                        );
+                    }
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::BackToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Back to menu",
+                                button_text_style(),
+                            ));
+                        });
+                });
+        });
+}
+
+fn end_daily(mut commands: Commands) {
+    commands.remove_resource::<DailyPuzzle>();
+}
@@ -0,0 +1,228 @@
+use bevy::{prelude::*, utils::Instant};
+
+use crate::generator::{Difficulty, GenerateError, Generator, GeneratorOptions};
+use crate::level_def::LevelDef;
+use crate::levels::{LevelAsset, LevelManager, LevelPack, Levels};
+use crate::rng::Rng;
+use crate::{GameState, Level, FRAME_BUDGET};
+
+// Endless mode plays generated levels one after another, each needing a few more moves than the
+// last. A run is played as a custom pack that grows a level at a time, so the rest of the game
+// treats it like any other pack. Everything is generated from the run's seed, so sharing the
+// seed lets someone else play the same levels.
+//
+// Long levels can take the generator a few seconds to find, too long to hold up the game for, so
+// the next level is generated a little each frame while the current one is being played. When no
+// level turns up the band is widened, and if that fails too the last level is played again.
+
+// Levels tried for each depth before widening the band, and again before giving up on it
+const ATTEMPTS: usize = 2000;
+
+// Present while an endless run is being played
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct EndlessRun {
+    pub seed: u64,
+}
+
+impl EndlessRun {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    // Generator settings for a depth, i.e the level number of the run starting at 1
+    pub fn options(depth: usize) -> GeneratorOptions {
+        // One more move needed per level until they match the hard pack. The band is left wide
+        // above that, as narrow bands of long solutions take the generator a long time to hit.
+        let fewest_moves = (5 + depth).min(35);
+        GeneratorOptions {
+            potions: (3 + depth / 5).min(6)..=(6 + depth / 3).min(10),
+            difficulty: Difficulty {
+                moves: fewest_moves..=fewest_moves * 3 / 2 + 5,
+                // Depth alone makes the run harder, any branching will do
+                branching: 0.0..=5.0,
+            },
+            attempts: ATTEMPTS,
+            // Levels are made while the game is running, so give up quickly on ones slow to solve
+            max_states: 30_000,
+            ..default()
+        }
+    }
+
+    // Each level gets a generator of its own, seeded from the run and depth, so it comes out the
+    // same however the run got there
+    pub fn generator(&self, depth: usize) -> DepthGenerator {
+        let rng = Rng::new(Rng::new(self.seed).next_u64() ^ depth as u64);
+        DepthGenerator::new(depth, Self::options(depth), rng)
+    }
+}
+
+// Generates the level for one depth of a run. When the attempts run out the band is widened, to
+// take levels down to half as long, and tried once more.
+#[derive(Debug)]
+pub struct DepthGenerator {
+    depth: usize,
+    options: GeneratorOptions,
+    generator: Generator,
+    rng: Rng,
+    widened: bool,
+}
+
+impl DepthGenerator {
+    pub fn new(depth: usize, options: GeneratorOptions, rng: Rng) -> Self {
+        Self {
+            depth,
+            generator: Generator::new(options.clone(), rng.clone()),
+            options,
+            rng,
+            widened: false,
+        }
+    }
+
+    // Whether nothing turned up in the band, so the widened one is being tried
+    pub fn is_widened(&self) -> bool {
+        self.widened
+    }
+
+    // Tries one more level, `None` while nothing has been found and there are attempts left. The
+    // level found is named after its depth.
+    pub fn step(&mut self) -> Option<Result<LevelDef, GenerateError>> {
+        match self.generator.step()? {
+            Ok(generated) => {
+                let mut level_def = generated.level;
+                level_def.name = format!("Depth {}", self.depth);
+                Some(Ok(level_def))
+            }
+            Err(GenerateError::NoLevelFound { .. }) if !self.widened => {
+                println!("Nothing found for depth {}, widening the band", self.depth);
+                let mut options = self.options.clone();
+                let moves = &options.difficulty.moves;
+                options.difficulty.moves = moves.start() / 2..=*moves.end();
+                self.generator = Generator::new(options, self.rng.clone());
+                self.widened = true;
+                None
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+// A level of the run while it is being generated, the next one after the level being played or
+// the first when starting a run
+#[derive(Resource, Debug)]
+pub struct PendingLevel(DepthGenerator);
+
+impl PendingLevel {
+    pub fn new(run: &EndlessRun, depth: usize) -> Self {
+        Self(run.generator(depth))
+    }
+}
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameSetup),
+            queue_next_level.run_if(resource_exists::<EndlessRun>()),
+        )
+        .add_systems(
+            Update,
+            generate_next_level.run_if(resource_exists::<PendingLevel>()),
+        )
+        .add_systems(OnEnter(GameState::Menu), end_run);
+    }
+}
+
+// Adds a level to the end of the run's pack. When none could be generated the last one is played
+// again, so the run can go on.
+fn push_level(
+    result: Result<LevelDef, GenerateError>,
+    depth: usize,
+    level_manager: &mut LevelManager,
+    level_assets: &mut Assets<LevelAsset>,
+    pack_assets: &mut Assets<LevelPack>,
+) {
+    let Some(pack) = level_manager
+        .custom
+        .as_ref()
+        .and_then(|handle| pack_assets.get(handle))
+    else {
+        return;
+    };
+
+    let level_def = match result {
+        Ok(level_def) => level_def,
+        Err(err) => {
+            println!("Could not generate depth {depth}: {err}");
+            let Some(last) = pack
+                .levels
+                .last()
+                .and_then(|handle| level_assets.get(handle))
+            else {
+                return;
+            };
+            let mut level_def = last.0.clone();
+            level_def.name = format!("Depth {depth}");
+            level_def
+        }
+    };
+
+    // A new pack rather than changing this one, which would look like it had been edited on disk
+    // and reload the level being played
+    let mut levels = pack.levels.clone();
+    levels.push(level_assets.add(LevelAsset(level_def)));
+    let pack = LevelPack {
+        title: pack.title.clone(),
+        author: pack.author.clone(),
+        levels,
+    };
+    level_manager.custom = Some(pack_assets.add(pack));
+}
+
+fn queue_next_level(
+    mut commands: Commands,
+    level: Res<Level>,
+    run: Res<EndlessRun>,
+    pending: Option<Res<PendingLevel>>,
+    levels: Levels,
+) {
+    // Already there, or on its way, when the level is restarted
+    let depth = level.number() + 1;
+    if levels.count() >= depth || pending.is_some_and(|pending| pending.0.depth == depth) {
+        return;
+    }
+
+    commands.insert_resource(PendingLevel::new(&run, depth));
+}
+
+// Works on the pending level for a little each frame. Moving on before it is ready waits for it
+// with the level summary showing.
+fn generate_next_level(
+    mut commands: Commands,
+    mut pending: ResMut<PendingLevel>,
+    mut level_manager: ResMut<LevelManager>,
+    mut level_assets: ResMut<Assets<LevelAsset>>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
+) {
+    let started = Instant::now();
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(result) = pending.0.step() else {
+            continue;
+        };
+
+        push_level(
+            result,
+            pending.0.depth,
+            &mut level_manager,
+            &mut level_assets,
+            &mut pack_assets,
+        );
+        commands.remove_resource::<PendingLevel>();
+        return;
+    }
+}
+
+fn end_run(mut commands: Commands) {
+    commands.remove_resource::<EndlessRun>();
+    commands.remove_resource::<PendingLevel>();
+}
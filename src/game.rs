@@ -2,6 +2,10 @@ use bevy::prelude::*;
 
 use crate::{
     button::{button_style, button_system, button_text_style, NORMAL_BUTTON},
+    clipboard,
+    daily::DailyPuzzle,
+    level_code,
+    levels::{LevelManager, LevelReloaded, Levels},
     map::{spawn_assets, spawn_map, AssetMap, TileMap},
     menu::MenuButtonAction,
     Level,
@@ -15,16 +19,13 @@ pub struct OnLevelCard;
 #[derive(Component, Debug)]
 pub struct OnGameCompleted;
 
+#[derive(Component, Debug)]
+pub struct OnLevelCode;
+
 // New type to use as a timer for the level card as a resource
 #[derive(Resource, Deref, DerefMut)]
 struct LevelCardTimer(Timer);
 
-#[derive(Component, Debug)]
-pub struct OnGameScreen;
-
-#[derive(Component, Debug)]
-pub struct OnGameLoad;
-
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -35,16 +36,35 @@ impl Plugin for GamePlugin {
         )
         .add_systems(Update, countdown.run_if(in_state(GameState::GameSetup)))
         .add_systems(OnExit(GameState::GameSetup), despawn_screen::<OnLevelCard>)
-        .add_systems(Update, game.run_if(in_state(GameState::GamePlay)))
+        .add_systems(
+            Update,
+            (game, export_level_code).run_if(in_state(GameState::GamePlay)),
+        )
+        // Rebuild the board in place when the level is edited while being played
+        .add_systems(
+            Update,
+            (
+                despawn_screen::<TileMap>,
+                despawn_screen::<AssetMap>,
+                spawn_map,
+                spawn_assets,
+            )
+                .chain()
+                .run_if(in_state(GameState::GamePlay).and_then(on_event::<LevelReloaded>())),
+        )
         .add_systems(
             OnExit(GameState::GamePlay),
             (
                 despawn_screen::<TileMap>,
                 despawn_screen::<AssetMap>,
-                game_levels_next,
+                despawn_screen::<OnLevelCode>,
             ),
         )
-        .add_systems(OnEnter(GameState::GameCompleted), game_levels_completed)
+        // The daily puzzle has a results screen of its own
+        .add_systems(
+            OnEnter(GameState::GameCompleted),
+            game_levels_completed.run_if(not(resource_exists::<DailyPuzzle>())),
+        )
         .add_systems(
             Update,
             (menu_action, button_system).run_if(in_state(GameState::GameCompleted)),
@@ -57,31 +77,127 @@ impl Plugin for GamePlugin {
     }
 }
 
-fn game_levels_next(mut level: ResMut<Level>) {
-    level.number += 1;
-    println!("Loading level {:?}", level.number);
-}
-
-fn game(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut game_state: ResMut<NextState<GameState>>,
-    mut level: ResMut<Level>,
-) {
+fn game(keyboard_input: Res<Input<KeyCode>>, mut game_state: ResMut<NextState<GameState>>) {
     if keyboard_input.just_pressed(KeyCode::Q) || keyboard_input.just_pressed(KeyCode::Escape) {
         game_state.set(GameState::Menu);
     }
     if keyboard_input.just_pressed(KeyCode::R) {
-        level.number -= 1;
+        // Setting up the level again restarts it, as levels only advance once completed
+        game_state.set(GameState::GameSetup);
+    }
+}
+
+// Moves on to the next level of the active pack, or finishes the game after the last one
+pub fn game_levels_next(level: &mut Level, levels: &Levels, game_state: &mut NextState<GameState>) {
+    if levels.is_last(level) {
+        println!("Game completed!");
+        game_state.set(GameState::GameCompleted);
+    } else {
+        println!("Level {} complete!", level.number());
+        level.index += 1;
         game_state.set(GameState::GameSetup);
     }
 }
 
-fn game_reset(mut level: ResMut<Level>) {
-    level.number = 1;
+fn game_reset(mut level: ResMut<Level>, mut level_manager: ResMut<LevelManager>) {
+    *level = Level::default();
+    level_manager.custom = None;
+}
+
+// Toggles an overlay showing the level code for the current level, copying it to the clipboard
+fn export_level_code(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    level: Res<Level>,
+    levels: Levels,
+    overlay_query: Query<Entity, With<OnLevelCode>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::E) {
+        return;
+    }
+
+    if !overlay_query.is_empty() {
+        for entity in &overlay_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let Some(level_def) = levels.get(&level) else {
+        return;
+    };
+
+    let code = level_code::encode(level_def);
+    println!("Level code: {code}");
+    let title = if clipboard::copy(&code) {
+        "Level code (copied to clipboard)"
+    } else {
+        "Level code"
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLevelCode,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        max_width: Val::Vw(90.0),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (text, font_size) in [(title, 20.0), (code.as_str(), 16.0)] {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                text,
+                                TextStyle {
+                                    font_size,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+                });
+        });
 }
 
-fn game_setup_complete(mut commands: Commands, level: Res<Level>) {
-    let level_text = format!("Level {}", level.number);
+fn game_setup_complete(mut commands: Commands, level: Res<Level>, levels: Levels) {
+    let level_def = levels.get(&level);
+
+    // Show the level name as the title when it has one, falling back to the level number
+    let mut level_text = format!("Level {}", level.number());
+    if let Some(author) = level_def.and_then(|level_def| level_def.author.as_ref()) {
+        level_text = format!("{} by {}", level_text, author);
+    }
+    let title_text = match level_def {
+        Some(level_def) if !level_def.name.is_empty() => level_def.name.clone(),
+        _ => std::mem::take(&mut level_text),
+    };
+    let hint_text = level_def
+        .and_then(|level_def| level_def.hint.clone())
+        .unwrap_or("Collect all the potions to complete level".into());
 
     commands
         .spawn((
@@ -110,10 +226,10 @@ fn game_setup_complete(mut commands: Commands, level: Res<Level>) {
                     ..default()
                 })
                 .with_children(|parent| {
-                    // Display the game name
+                    // Display the level name
                     parent.spawn(
                         TextBundle::from_section(
-                            level_text,
+                            title_text,
                             TextStyle {
                                 font_size: 80.0,
                                 color: Color::WHITE,
@@ -125,9 +241,26 @@ fn game_setup_complete(mut commands: Commands, level: Res<Level>) {
                             ..default()
                         }),
                     );
+                    // The level number, unless it is already the title
+                    if !level_text.is_empty() {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                level_text,
+                                TextStyle {
+                                    font_size: 30.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            }),
+                        );
+                    }
                     parent.spawn(
                         TextBundle::from_section(
-                            "Collect all the potions to complete level",
+                            hint_text,
                             TextStyle {
                                 font_size: 20.0,
                                 color: Color::WHITE,
@@ -0,0 +1,234 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::level_def::{parse_level, LevelDef, LevelRules, Tile};
+use crate::rng::Rng;
+use crate::sim::{Board, Direction, Move};
+use crate::solver::solve_with_limit;
+
+// Makes random levels, keeping only the ones the solver proves can be finished within a band of
+// difficulty. Difficulty is judged on the length of the shortest solution, and on branching: the
+// average number of moves that go somewhere at each step of that solution. The more there are to
+// choose from, the harder the right one is to spot.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    pub moves: RangeInclusive<usize>,
+    pub branching: RangeInclusive<f32>,
+}
+
+impl Difficulty {
+    // Random boards nearly all give between 3.2 and 3.8 moves to choose from, whatever the length
+    // of their solution, so the presets go on length alone. Branching is there to narrow down by
+    // hand.
+    pub const EASY: Difficulty = Difficulty {
+        moves: 6..=15,
+        branching: 0.0..=5.0,
+    };
+    pub const MEDIUM: Difficulty = Difficulty {
+        moves: 16..=30,
+        branching: 0.0..=5.0,
+    };
+    pub const HARD: Difficulty = Difficulty {
+        moves: 31..=60,
+        branching: 0.0..=5.0,
+    };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorOptions {
+    pub width: usize,
+    pub height: usize,
+    pub potions: RangeInclusive<usize>,
+    // Chance of each tile inside the border being a wall, from 0 to 1
+    pub wall_density: f32,
+    pub difficulty: Difficulty,
+    // Levels to try before giving up
+    pub attempts: usize,
+    // How long the solver gets on each level, harder ones are thrown away
+    pub max_states: usize,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            width: 12,
+            height: 9,
+            potions: 4..=10,
+            wall_density: 0.2,
+            difficulty: Difficulty::MEDIUM,
+            attempts: 2000,
+            max_states: 200_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    // Not enough room inside the border for the player, teleporter and potions
+    TooSmall,
+    NoLevelFound { attempts: usize },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::TooSmall => {
+                write!(
+                    f,
+                    "board is too small for the player, teleporter and potions"
+                )
+            }
+            GenerateError::NoLevelFound { attempts } => {
+                write!(
+                    f,
+                    "no level of that difficulty found in {attempts} attempts"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+#[derive(Clone, Debug)]
+pub struct Generated {
+    // Named "Generated level" with its par set to the length of `solution`
+    pub level: LevelDef,
+    pub solution: Vec<Move>,
+    pub branching: f32,
+}
+
+// Generates a level one attempt at a time, so the work can be spread over several frames
+#[derive(Clone, Debug)]
+pub struct Generator {
+    options: GeneratorOptions,
+    rng: Rng,
+    attempts: usize,
+}
+
+impl Generator {
+    pub fn new(options: GeneratorOptions, rng: Rng) -> Self {
+        Self {
+            options,
+            rng,
+            attempts: 0,
+        }
+    }
+
+    // Tries one more level, `None` while nothing has been found and there are attempts left
+    pub fn step(&mut self) -> Option<Result<Generated, GenerateError>> {
+        let options = &self.options;
+        let inside = options.width.saturating_sub(2) * options.height.saturating_sub(2);
+        if inside < options.potions.start() + 2 || options.potions.is_empty() {
+            return Some(Err(GenerateError::TooSmall));
+        }
+        if self.attempts >= options.attempts {
+            return Some(Err(GenerateError::NoLevelFound {
+                attempts: options.attempts,
+            }));
+        }
+        self.attempts += 1;
+
+        let mut level = random_level(options, &mut self.rng)?;
+        let solution = solve_with_limit(&Board::new(&level), options.max_states).ok()?;
+        if !options.difficulty.moves.contains(&solution.moves.len()) {
+            return None;
+        }
+        let branching = branching(&level, &solution.moves);
+        if !options.difficulty.branching.contains(&branching) {
+            return None;
+        }
+
+        level.par = Some(solution.moves.len() as u32);
+        Some(Ok(Generated {
+            level,
+            solution: solution.moves,
+            branching,
+        }))
+    }
+
+    // Keeps trying until a level is found or the attempts run out
+    pub fn finish(&mut self) -> Result<Generated, GenerateError> {
+        loop {
+            if let Some(result) = self.step() {
+                return result;
+            }
+        }
+    }
+}
+
+pub fn generate(options: &GeneratorOptions, rng: &mut Rng) -> Result<Generated, GenerateError> {
+    let mut generator = Generator::new(options.clone(), rng.clone());
+    let result = generator.finish();
+    *rng = generator.rng;
+    result
+}
+
+// Average number of moves that go somewhere, over the states the solution passes through
+pub fn branching(level: &LevelDef, solution: &[Move]) -> f32 {
+    let moves: Vec<Move> = Direction::ALL
+        .map(Move::Slide)
+        .into_iter()
+        .chain([Move::Teleport])
+        .collect();
+
+    let mut board = Board::new(level);
+    let mut choices = 0;
+    for mv in solution {
+        choices += moves
+            .iter()
+            .filter(|candidate| board.clone().apply_move(**candidate).moved())
+            .count();
+        board.apply_move(*mv);
+    }
+
+    choices as f32 / solution.len().max(1) as f32
+}
+
+// A walled in board with random walls inside, and the player, teleporter and potions dropped on
+// random floor tiles. `None` if the walls left too little floor.
+fn random_level(options: &GeneratorOptions, rng: &mut Rng) -> Option<LevelDef> {
+    let (width, height) = (options.width, options.height);
+    let mut tiles = vec![Tile::Wall; width * height];
+    let mut floor = Vec::new();
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            if !rng.chance(options.wall_density) {
+                tiles[y * width + x] = Tile::Floor;
+                floor.push(y * width + x);
+            }
+        }
+    }
+
+    let potions = rng.range(options.potions.clone());
+    if floor.len() < potions + 2 {
+        return None;
+    }
+
+    // Partial shuffle, the first few floor tiles are the ones that get something on them
+    for idx in 0..potions + 2 {
+        let other = idx + rng.below(floor.len() - idx);
+        floor.swap(idx, other);
+    }
+    tiles[floor[0]] = Tile::Player;
+    tiles[floor[1]] = Tile::Teleporter;
+    for idx in &floor[2..potions + 2] {
+        tiles[*idx] = Tile::Potion;
+    }
+
+    let level = LevelDef {
+        name: "Generated level".into(),
+        author: None,
+        hint: None,
+        par: None,
+        music: None,
+        rules: LevelRules::default(),
+        width,
+        height,
+        tiles,
+    };
+
+    // Holds the generated level to the same checks as level files
+    parse_level(&level.to_string()).ok()
+}
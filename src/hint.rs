@@ -0,0 +1,200 @@
+use bevy::{prelude::*, utils::Instant};
+
+use crate::ascii::TILE_SIZE;
+use crate::map::{BoardState, MapSize};
+use crate::movement::{MoveQueue, Moveable};
+use crate::playback::SolutionPlayback;
+use crate::sim::{Board, Cell, Direction, Move};
+use crate::solver::{SolveError, Solver};
+use crate::{GameState, FRAME_BUDGET};
+
+// Pressing H solves the level from where the wizard is and points out the next move of the
+// shortest solution: an arrow on the wizard for a slide, or a box around the teleporter for a
+// swap. When there is no way left to win a cross is drawn on the wizard instead, undoing is the
+// only way out. The solver is given a little time each frame, and the hint drawn over the board
+// once it is found, until the board changes.
+
+// Hints are wanted while playing, so give up before they take too long to turn up
+const HINT_MAX_STATES: usize = 200_000;
+
+const HINT_COLOR: Color = Color::YELLOW;
+const STUCK_COLOR: Color = Color::RED;
+
+// Hints given on the level being played
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HintsUsed(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HintKind {
+    Move(Move),
+    Stuck,
+}
+
+// The hint being shown, and the board it is for
+#[derive(Resource, Debug)]
+struct ShownHint {
+    board: Board,
+    kind: HintKind,
+}
+
+// A hint being solved for, and the board it is for
+#[derive(Resource, Debug)]
+struct PendingHint {
+    board: Board,
+    solver: Solver,
+}
+
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HintsUsed>()
+            .add_systems(OnEnter(GameState::GameSetup), reset_hints)
+            .add_systems(
+                Update,
+                (
+                    find_hint.run_if(not(resource_exists::<SolutionPlayback>())),
+                    solve_hint.run_if(resource_exists::<PendingHint>()),
+                    draw_hint,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
+            );
+    }
+}
+
+fn reset_hints(mut commands: Commands, mut hints_used: ResMut<HintsUsed>) {
+    *hints_used = HintsUsed::default();
+    commands.remove_resource::<ShownHint>();
+    commands.remove_resource::<PendingHint>();
+}
+
+fn find_hint(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    shown: Option<Res<ShownHint>>,
+    pending: Option<Res<PendingHint>>,
+    player_query: Query<&Moveable>,
+    move_queue: Res<MoveQueue>,
+    board: Option<Res<BoardState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::H) {
+        return;
+    }
+
+    let (Ok(moveable), Some(board)) = (player_query.get_single(), board) else {
+        return;
+    };
+
+    // Only for a board that has settled, and that doesn't already have one
+    if moveable.is_moving() || !move_queue.is_empty() || board.is_won() {
+        return;
+    }
+    if shown.is_some_and(|shown| shown.board == board.0)
+        || pending.is_some_and(|pending| pending.board == board.0)
+    {
+        return;
+    }
+
+    commands.insert_resource(PendingHint {
+        board: board.0.clone(),
+        solver: Solver::new(&board, HINT_MAX_STATES),
+    });
+}
+
+fn solve_hint(
+    mut commands: Commands,
+    mut pending: ResMut<PendingHint>,
+    mut hints_used: ResMut<HintsUsed>,
+    board: Option<Res<BoardState>>,
+) {
+    // The board has changed since, a move or undo, so the hint would be for the wrong one
+    if !board.is_some_and(|board| board.0 == pending.board) {
+        commands.remove_resource::<PendingHint>();
+        return;
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(result) = pending.solver.step() else {
+            continue;
+        };
+        commands.remove_resource::<PendingHint>();
+
+        let kind = match result {
+            Ok(solution) => {
+                let Some(mv) = solution.moves.first() else {
+                    return;
+                };
+                println!("Hint: {mv}");
+                HintKind::Move(*mv)
+            }
+            Err(SolveError::Unsolvable { .. }) => {
+                println!("Hint: no way to win from here, undo with Z");
+                HintKind::Stuck
+            }
+            Err(err) => {
+                println!("Could not find a hint: {err}");
+                return;
+            }
+        };
+
+        hints_used.0 += 1;
+        commands.insert_resource(ShownHint {
+            board: pending.board.clone(),
+            kind,
+        });
+        return;
+    }
+}
+
+fn draw_hint(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    shown: Option<Res<ShownHint>>,
+    map_size: Option<Res<MapSize>>,
+    board: Option<Res<BoardState>>,
+) {
+    let (Some(shown), Some(map_size), Some(board)) = (shown, map_size, board) else {
+        return;
+    };
+
+    // Anything that changes the board, a move, undo or the level being edited, leaves the hint
+    // pointing at the wrong thing
+    if shown.board != board.0 {
+        commands.remove_resource::<ShownHint>();
+        return;
+    }
+
+    let centre = |cell: Cell| map_size.translation(cell.x, cell.y, 0.0).truncate();
+    let player = centre(board.player());
+    match shown.kind {
+        HintKind::Move(Move::Slide(direction)) => {
+            let towards = match direction {
+                Direction::Up => Vec2::Y,
+                Direction::Down => Vec2::NEG_Y,
+                Direction::Left => Vec2::NEG_X,
+                Direction::Right => Vec2::X,
+            };
+            let tip = player + towards * TILE_SIZE * 0.4;
+            let head = towards * TILE_SIZE * 0.2;
+            gizmos.line_2d(player - towards * TILE_SIZE * 0.4, tip, HINT_COLOR);
+            gizmos.line_2d(tip, tip - head + head.perp(), HINT_COLOR);
+            gizmos.line_2d(tip, tip - head - head.perp(), HINT_COLOR);
+        }
+        HintKind::Move(Move::Teleport) => {
+            gizmos.rect_2d(
+                centre(board.teleporter()),
+                0.0,
+                Vec2::splat(TILE_SIZE),
+                HINT_COLOR,
+            );
+        }
+        HintKind::Stuck => {
+            let corner = Vec2::splat(TILE_SIZE * 0.4);
+            gizmos.line_2d(player - corner, player + corner, STUCK_COLOR);
+            let corner = Vec2::new(corner.x, -corner.y);
+            gizmos.line_2d(player - corner, player + corner, STUCK_COLOR);
+        }
+    }
+}
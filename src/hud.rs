@@ -1,22 +1,55 @@
 use bevy::prelude::*;
 
-use crate::{despawn_screen, GameState, Level};
+use crate::{
+    despawn_screen,
+    endless::EndlessRun,
+    levels::Levels,
+    movement::{GameTick, LevelStats},
+    summary::{format_time, LevelPar},
+    GameState, Level,
+};
 
 #[derive(Component, Debug)]
 struct OnHud;
 
+// Tag component for the text showing moves, teleports and time, its second section is kept up to
+// date while playing
+#[derive(Component, Debug)]
+struct HudStats;
+
 #[derive(Component, Debug)]
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::GameSetup), spawn_hud)
+            .add_systems(
+                Update,
+                update_hud_stats.run_if(in_state(GameState::GamePlay)),
+            )
             .add_systems(OnExit(GameState::GamePlay), despawn_screen::<OnHud>);
     }
 }
 
-fn spawn_hud(mut commands: Commands, level: Res<Level>) {
-    let level_text = format!("Level {} of 10", level.number);
+fn spawn_hud(
+    mut commands: Commands,
+    level: Res<Level>,
+    levels: Levels,
+    endless: Option<Res<EndlessRun>>,
+) {
+    let level_text = match (endless, levels.get(&level)) {
+        // An endless run has no end to count towards, show how far in it is instead
+        (Some(run), _) => format!("Endless depth {} (seed {})", level.number(), run.seed),
+        (None, Some(level_def)) if !level_def.name.is_empty() => {
+            format!(
+                "Level {} of {}: {}",
+                level.number(),
+                levels.count(),
+                level_def.name
+            )
+        }
+        _ => format!("Level {} of {}", level.number(), levels.count()),
+    };
 
     commands
         .spawn((
@@ -44,25 +77,28 @@ fn spawn_hud(mut commands: Commands, level: Res<Level>) {
                     ..default()
                 })
                 .with_children(|parent| {
-                    // Display level info
-                    parent.spawn(
-                        TextBundle::from_section(
-                            level_text,
-                            TextStyle {
-                                font_size: 20.0,
-                                color: Color::WHITE,
-                                ..default()
-                            },
-                        )
+                    // Display level info, and how the level is going underneath
+                    let text_style = TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    };
+                    parent.spawn((
+                        TextBundle::from_sections([
+                            TextSection::new(level_text, text_style.clone()),
+                            TextSection::new("", text_style),
+                        ])
                         .with_style(Style {
                             margin: UiRect::all(Val::Px(10.0)),
                             ..default()
-                        }),
-                    );
+                        })
+                        .with_text_alignment(TextAlignment::Center),
+                        HudStats,
+                    ));
                     // Display controlls
                     parent.spawn(
                         TextBundle::from_section(
-                            "Move: ARROW KEYS, Teleport: SPACE, Music: M, Reset: R, Quit: Q",
+                            "Move: ARROW KEYS, Teleport: SPACE, Undo: Z, Redo: Y, Hint: H, Solution: S, Music: M, Reset: R, Code: E, Quit: Q",
                             TextStyle {
                                 font_size: 20.0,
                                 color: Color::WHITE,
@@ -77,3 +113,27 @@ fn spawn_hud(mut commands: Commands, level: Res<Level>) {
                 });
         });
 }
+
+fn update_hud_stats(
+    mut stats_query: Query<&mut Text, With<HudStats>>,
+    level_stats: Res<LevelStats>,
+    level_par: Res<LevelPar>,
+    game_tick: Res<GameTick>,
+) {
+    let mut stats = format!(
+        "\nMoves: {}  Teleports: {}  Time: {}",
+        level_stats.moves,
+        level_stats.teleports,
+        format_time(game_tick.seconds())
+    );
+    if let Some(par) = level_par.0 {
+        stats = format!("{stats}  Par: {par}");
+    }
+
+    // Only touch the text when it changes, so it isn't laid out again every frame
+    for mut text in &mut stats_query {
+        if text.sections[1].value != stats {
+            text.sections[1].value = stats.clone();
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::fmt;
+
+use crate::level_def::{parse_level, LevelDef, LevelError, LevelRules, Tile};
+
+// Level codes are a compact, URL safe way of sharing a level (map and metadata) as a single line
+// of text. The level is packed into bytes, followed by a CRC-16 checksum, and base64url encoded:
+//
+//   version, width, height, flags, par, name, author, hint, music,
+//   player position, teleporter position, interior tiles (2 bits each), checksum
+//
+// Numbers are LEB128 varints and strings are length prefixed UTF-8. The border is always wall so
+// only the interior tiles are stored, with the player and teleporter standing on floor.
+
+const VERSION: u8 = 1;
+const FLAG_NO_TELEPORT: u8 = 1;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LevelCodeError {
+    InvalidCharacter(char),
+    Truncated,
+    Checksum,
+    UnsupportedVersion(u8),
+    Malformed,
+    InvalidLevel(Vec<LevelError>),
+}
+
+impl fmt::Display for LevelCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelCodeError::InvalidCharacter(c) => write!(f, "`{c}` is not part of a level code"),
+            LevelCodeError::Truncated => write!(f, "level code is incomplete"),
+            LevelCodeError::Checksum => write!(f, "level code checksum does not match"),
+            LevelCodeError::UnsupportedVersion(version) => {
+                write!(f, "level code version {version} is not supported")
+            }
+            LevelCodeError::Malformed => write!(f, "level code is malformed"),
+            LevelCodeError::InvalidLevel(errors) => {
+                write!(f, "level code contains an invalid level")?;
+                for err in errors {
+                    write!(f, "\n  {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelCodeError {}
+
+pub fn encode(level: &LevelDef) -> String {
+    let mut bytes = vec![VERSION];
+    write_varint(&mut bytes, level.width);
+    write_varint(&mut bytes, level.height);
+    bytes.push(if level.rules.teleport {
+        0
+    } else {
+        FLAG_NO_TELEPORT
+    });
+    write_varint(&mut bytes, level.par.map_or(0, |par| par as usize + 1));
+    write_str(&mut bytes, &level.name);
+    write_str(&mut bytes, level.author.as_deref().unwrap_or_default());
+    write_str(&mut bytes, level.hint.as_deref().unwrap_or_default());
+    write_str(&mut bytes, level.music.as_deref().unwrap_or_default());
+
+    let position = |wanted| level.tiles.iter().position(|tile| *tile == wanted);
+    write_varint(&mut bytes, position(Tile::Player).unwrap_or(0));
+    write_varint(&mut bytes, position(Tile::Teleporter).unwrap_or(0));
+
+    let mut bits = BitWriter::default();
+    for y in 1..level.height.saturating_sub(1) {
+        for x in 1..level.width.saturating_sub(1) {
+            bits.write(match level.tile(x, y) {
+                Tile::Wall => 1,
+                Tile::Potion => 2,
+                _ => 0,
+            });
+        }
+    }
+    bytes.extend(bits.finish());
+
+    to_code(bytes)
+}
+
+// Adds the checksum to the end of the bytes and writes them out as a code
+fn to_code(mut bytes: Vec<u8>) -> String {
+    bytes.extend(crc16(&bytes).to_be_bytes());
+    to_base64(&bytes)
+}
+
+pub fn decode(code: &str) -> Result<LevelDef, LevelCodeError> {
+    let bytes = from_base64(code)?;
+    let (payload, checksum) = bytes
+        .split_last_chunk::<2>()
+        .ok_or(LevelCodeError::Truncated)?;
+    if crc16(payload) != u16::from_be_bytes(*checksum) {
+        return Err(LevelCodeError::Checksum);
+    }
+
+    let mut reader = ByteReader { bytes: payload };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(LevelCodeError::UnsupportedVersion(version));
+    }
+
+    let width = reader.varint()?;
+    let height = reader.varint()?;
+    let flags = reader.byte()?;
+    let par = reader.varint()?;
+    let name = reader.string()?;
+    let author = reader.string()?;
+    let hint = reader.string()?;
+    let music = reader.string()?;
+    let player = reader.varint()?;
+    let teleporter = reader.varint()?;
+
+    // The player has to stand inside the border, so anything smaller can't be a level
+    if width < 3 || height < 3 {
+        return Err(LevelCodeError::Malformed);
+    }
+
+    // Sizes come from whoever made the code, so check the tiles are really there before making
+    // room for them. That bounds the size by the length of the code.
+    let interior = (width - 2)
+        .checked_mul(height - 2)
+        .ok_or(LevelCodeError::Malformed)?;
+    if interior.div_ceil(4) > reader.bytes.len() {
+        return Err(LevelCodeError::Truncated);
+    }
+
+    let num_tiles = width * height;
+    if player >= num_tiles || teleporter >= num_tiles {
+        return Err(LevelCodeError::Malformed);
+    }
+
+    let mut tiles = vec![Tile::Wall; num_tiles];
+    let mut bits = BitReader::new(reader.bytes);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            tiles[y * width + x] = match bits.read().ok_or(LevelCodeError::Truncated)? {
+                0 => Tile::Floor,
+                1 => Tile::Wall,
+                2 => Tile::Potion,
+                _ => return Err(LevelCodeError::Malformed),
+            };
+        }
+    }
+    tiles[player] = Tile::Player;
+    tiles[teleporter] = Tile::Teleporter;
+
+    let optional = |value: String| (!value.is_empty()).then_some(value);
+    let level = LevelDef {
+        name,
+        author: optional(author),
+        hint: optional(hint),
+        par: par.checked_sub(1).map(|par| par as u32),
+        music: optional(music),
+        rules: LevelRules {
+            teleport: flags & FLAG_NO_TELEPORT == 0,
+        },
+        width,
+        height,
+        tiles,
+    };
+
+    // Round trip through the level format, so shared levels get the same checks as level files
+    parse_level(&level.to_string()).map_err(LevelCodeError::InvalidLevel)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_str(bytes: &mut Vec<u8>, value: &str) {
+    write_varint(bytes, value.len());
+    bytes.extend(value.as_bytes());
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn byte(&mut self) -> Result<u8, LevelCodeError> {
+        let (byte, rest) = self.bytes.split_first().ok_or(LevelCodeError::Truncated)?;
+        self.bytes = rest;
+        Ok(*byte)
+    }
+
+    fn varint(&mut self) -> Result<usize, LevelCodeError> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or(LevelCodeError::Malformed)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(LevelCodeError::Malformed)
+    }
+
+    fn string(&mut self) -> Result<String, LevelCodeError> {
+        let len = self.varint()?;
+        if len > self.bytes.len() {
+            return Err(LevelCodeError::Truncated);
+        }
+        let (value, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        // Metadata is written one field per line, so line breaks can't be allowed through
+        let value = std::str::from_utf8(value).map_err(|_| LevelCodeError::Malformed)?;
+        Ok(value.replace(['\n', '\r'], " ").trim().to_string())
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u32,
+}
+
+impl BitWriter {
+    // Appends a 2 bit value
+    fn write(&mut self, value: u8) {
+        let shift = 6 - (self.used % 8);
+        if shift == 6 {
+            self.bytes.push(0);
+        }
+        if let Some(last) = self.bytes.last_mut() {
+            *last |= (value & 0b11) << shift;
+        }
+        self.used += 2;
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    read: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, read: 0 }
+    }
+
+    // Reads the next 2 bit value
+    fn read(&mut self) -> Option<u8> {
+        let byte = self.bytes.get(self.read / 8)?;
+        let shift = 6 - (self.read % 8);
+        self.read += 2;
+        Some((byte >> shift) & 0b11)
+    }
+}
+
+// CRC-16/CCITT-FALSE
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, byte)| {
+            group | (*byte as u32) << (16 - idx * 8)
+        });
+        for idx in 0..=chunk.len() {
+            code.push(ALPHABET[(group >> (18 - idx * 6)) as usize & 0x3f] as char);
+        }
+    }
+    code
+}
+
+fn from_base64(code: &str) -> Result<Vec<u8>, LevelCodeError> {
+    let mut bytes = Vec::new();
+    let mut group = 0u32;
+    let mut bits = 0;
+
+    for c in code.chars().filter(|c| !c.is_whitespace()) {
+        let value = ALPHABET
+            .iter()
+            .position(|a| *a as char == c)
+            .ok_or(LevelCodeError::InvalidCharacter(c))?;
+        group = (group << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((group >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_claiming_more_tiles_than_they_hold_are_truncated() {
+        let mut bytes = vec![VERSION];
+        write_varint(&mut bytes, 1 << 30);
+        write_varint(&mut bytes, 1 << 30);
+        // Flags, par, the four strings, and the player and teleporter, then no tiles at all
+        bytes.extend([0; 8]);
+        assert_eq!(decode(&to_code(bytes)), Err(LevelCodeError::Truncated));
+    }
+}
@@ -0,0 +1,493 @@
+use std::fmt;
+
+// A level file is an optional metadata header followed by the ASCII map, e.g.
+//
+//   name: The Cellar
+//   author: Paul Cockrell
+//   hint: Slide through a bottle to pick it up
+//   par: 12
+//   music: dungeon-level.ogg
+//   rules: no-teleport
+//   size: 12x3
+//   ---
+//   ############
+//   #..p..t.o..#
+//   ############
+//
+// Files without a `---` separator are treated as a bare map. When no size is declared it is taken
+// from the map itself, i.e the width of the first row and the number of rows.
+
+pub const HEADER_SEPARATOR: &str = "---";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tile {
+    Wall,
+    Floor,
+    Potion,
+    Player,
+    Teleporter,
+}
+
+impl Tile {
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '#' => Some(Tile::Wall),
+            '.' => Some(Tile::Floor),
+            'o' => Some(Tile::Potion),
+            'p' => Some(Tile::Player),
+            't' => Some(Tile::Teleporter),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Floor => '.',
+            Tile::Potion => 'o',
+            Tile::Player => 'p',
+            Tile::Teleporter => 't',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LevelRules {
+    // Whether the player is allowed to swap places with the teleporter
+    pub teleport: bool,
+}
+
+impl Default for LevelRules {
+    fn default() -> Self {
+        Self { teleport: true }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LevelDef {
+    pub name: String,
+    pub author: Option<String>,
+    pub hint: Option<String>,
+    pub par: Option<u32>,
+    pub music: Option<String>,
+    pub rules: LevelRules,
+    pub width: usize,
+    pub height: usize,
+    // Row major, `width * height` tiles starting from the top left
+    pub tiles: Vec<Tile>,
+}
+
+impl LevelDef {
+    pub fn tile(&self, x: usize, y: usize) -> Tile {
+        self.tiles[y * self.width + x]
+    }
+}
+
+// Writes the level back out in the level file format
+impl fmt::Display for LevelDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.name.is_empty() {
+            writeln!(f, "name: {}", self.name)?;
+        }
+        if let Some(author) = &self.author {
+            writeln!(f, "author: {author}")?;
+        }
+        if let Some(hint) = &self.hint {
+            writeln!(f, "hint: {hint}")?;
+        }
+        if let Some(par) = self.par {
+            writeln!(f, "par: {par}")?;
+        }
+        if let Some(music) = &self.music {
+            writeln!(f, "music: {music}")?;
+        }
+        if !self.rules.teleport {
+            writeln!(f, "rules: no-teleport")?;
+        }
+        writeln!(f, "{HEADER_SEPARATOR}")?;
+
+        for row in self.tiles.chunks(self.width.max(1)) {
+            let row: String = row.iter().map(|tile| tile.to_char()).collect();
+            writeln!(f, "{row}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LevelErrorKind {
+    MalformedHeader,
+    UnknownHeaderKey(String),
+    InvalidPar(String),
+    UnknownRule(String),
+    InvalidSize(String),
+    WrongWidth { expected: usize, found: usize },
+    WrongHeight { expected: usize, found: usize },
+    UnknownCharacter(char),
+    MissingPlayer,
+    DuplicatePlayer,
+    MissingTeleporter,
+    DuplicateTeleporter,
+    OpenBorder,
+    NoPotions,
+    EmptyPack,
+}
+
+impl fmt::Display for LevelErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelErrorKind::MalformedHeader => write!(f, "expected `key: value` in header"),
+            LevelErrorKind::UnknownHeaderKey(key) => write!(f, "unknown header key `{key}`"),
+            LevelErrorKind::InvalidPar(par) => write!(f, "par `{par}` is not a move count"),
+            LevelErrorKind::UnknownRule(rule) => write!(f, "unknown rule `{rule}`"),
+            LevelErrorKind::InvalidSize(size) => {
+                write!(f, "size `{size}` is not in the form `WIDTHxHEIGHT`")
+            }
+            LevelErrorKind::WrongWidth { expected, found } => {
+                write!(f, "row is {found} tiles wide, expected {expected}")
+            }
+            LevelErrorKind::WrongHeight { expected, found } => {
+                write!(f, "map is {found} rows high, expected {expected}")
+            }
+            LevelErrorKind::UnknownCharacter(c) => write!(f, "unknown tile `{c}`"),
+            LevelErrorKind::MissingPlayer => write!(f, "map has no player `p`"),
+            LevelErrorKind::DuplicatePlayer => write!(f, "map has more than one player `p`"),
+            LevelErrorKind::MissingTeleporter => write!(f, "map has no teleporter `t`"),
+            LevelErrorKind::DuplicateTeleporter => {
+                write!(f, "map has more than one teleporter `t`")
+            }
+            LevelErrorKind::OpenBorder => write!(f, "border tile must be a wall `#`"),
+            LevelErrorKind::NoPotions => write!(f, "map has no potions `o`"),
+            LevelErrorKind::EmptyPack => write!(f, "pack does not list any levels"),
+        }
+    }
+}
+
+// A problem found while parsing a level, lines and columns start at 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: LevelErrorKind,
+}
+
+impl LevelError {
+    fn new(line: usize, column: usize, kind: LevelErrorKind) -> Self {
+        Self { line, column, kind }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+// Parses a level file, collecting every problem found rather than stopping at the first
+pub fn parse_level(source: &str) -> Result<LevelDef, Vec<LevelError>> {
+    let mut errors = Vec::new();
+    let mut level = LevelDef {
+        name: String::new(),
+        author: None,
+        hint: None,
+        par: None,
+        music: None,
+        rules: LevelRules::default(),
+        width: 0,
+        height: 0,
+        tiles: Vec::new(),
+    };
+
+    let lines: Vec<(usize, &str)> = source
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim_end()))
+        .collect();
+
+    let (map_lines, size) = match lines.iter().position(|(_, line)| *line == HEADER_SEPARATOR) {
+        Some(separator) => {
+            let size = parse_header(&lines[..separator], &mut level, &mut errors);
+            (&lines[separator + 1..], size)
+        }
+        None => (&lines[..], None),
+    };
+
+    let rows: Vec<(usize, &str)> = map_lines
+        .iter()
+        .copied()
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+
+    (level.width, level.height) = match size {
+        // The size is whatever the file says, so check the map really is that big before making
+        // room for it
+        Some(size) => {
+            let widest = rows.iter().map(|(_, row)| row.chars().count()).max();
+            let kind = if size.width.checked_mul(size.height).is_none() || size.height != rows.len()
+            {
+                Some(LevelErrorKind::WrongHeight {
+                    expected: size.height,
+                    found: rows.len(),
+                })
+            } else if size.width > widest.unwrap_or(0) {
+                Some(LevelErrorKind::WrongWidth {
+                    expected: size.width,
+                    found: widest.unwrap_or(0),
+                })
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                errors.push(LevelError::new(size.line, size.column, kind));
+                return Err(errors);
+            }
+            (size.width, size.height)
+        }
+        None => {
+            let width = rows.first().map(|(_, row)| row.chars().count());
+            (width.unwrap_or(0), rows.len())
+        }
+    };
+
+    parse_map(&rows, &mut level, &mut errors);
+
+    if errors.is_empty() {
+        Ok(level)
+    } else {
+        Err(errors)
+    }
+}
+
+// A map size given in the header, and where
+struct DeclaredSize {
+    width: usize,
+    height: usize,
+    line: usize,
+    column: usize,
+}
+
+// Fills in the level metadata, returning the declared map size if there is one
+fn parse_header(
+    lines: &[(usize, &str)],
+    level: &mut LevelDef,
+    errors: &mut Vec<LevelError>,
+) -> Option<DeclaredSize> {
+    let mut size = None;
+
+    for &(line_no, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            errors.push(LevelError::new(line_no, 1, LevelErrorKind::MalformedHeader));
+            continue;
+        };
+        let value_column = key.len() + 2 + (value.len() - value.trim_start().len());
+        let value = value.trim();
+
+        match key.trim() {
+            "name" => level.name = value.to_string(),
+            "author" => level.author = Some(value.to_string()),
+            "hint" => level.hint = Some(value.to_string()),
+            "music" => level.music = Some(value.to_string()),
+            "par" => match value.parse() {
+                Ok(par) => level.par = Some(par),
+                Err(_) => errors.push(LevelError::new(
+                    line_no,
+                    value_column,
+                    LevelErrorKind::InvalidPar(value.to_string()),
+                )),
+            },
+            "size" => match parse_size(value) {
+                Some((width, height)) => {
+                    size = Some(DeclaredSize {
+                        width,
+                        height,
+                        line: line_no,
+                        column: value_column,
+                    })
+                }
+                None => errors.push(LevelError::new(
+                    line_no,
+                    value_column,
+                    LevelErrorKind::InvalidSize(value.to_string()),
+                )),
+            },
+            "rules" => {
+                let mut column = value_column;
+                for rule in value.split(',') {
+                    let offset = rule.len() - rule.trim_start().len();
+                    match rule.trim() {
+                        "no-teleport" => level.rules.teleport = false,
+                        "" => (),
+                        unknown => errors.push(LevelError::new(
+                            line_no,
+                            column + offset,
+                            LevelErrorKind::UnknownRule(unknown.to_string()),
+                        )),
+                    }
+                    column += rule.len() + 1;
+                }
+            }
+            unknown => errors.push(LevelError::new(
+                line_no,
+                1,
+                LevelErrorKind::UnknownHeaderKey(unknown.to_string()),
+            )),
+        }
+    }
+
+    size
+}
+
+fn parse_size(value: &str) -> Option<(usize, usize)> {
+    let (width, height) = value.split_once('x')?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+fn parse_map(rows: &[(usize, &str)], level: &mut LevelDef, errors: &mut Vec<LevelError>) {
+    let (width, height) = (level.width, level.height);
+    // Where to report problems that belong to the map as a whole
+    let map_line = rows.first().map(|(line_no, _)| *line_no).unwrap_or(1);
+
+    let mut tiles = vec![Tile::Floor; width * height];
+    let mut player = None;
+    let mut teleporter = None;
+    let mut potions = 0;
+
+    for (y, &(line_no, row)) in rows.iter().enumerate() {
+        let found = row.chars().count();
+        if found != width {
+            errors.push(LevelError::new(
+                line_no,
+                found.min(width) + 1,
+                LevelErrorKind::WrongWidth {
+                    expected: width,
+                    found,
+                },
+            ));
+        }
+
+        for (x, c) in row.chars().enumerate() {
+            let column = x + 1;
+            let Some(tile) = Tile::from_char(c) else {
+                errors.push(LevelError::new(
+                    line_no,
+                    column,
+                    LevelErrorKind::UnknownCharacter(c),
+                ));
+                continue;
+            };
+
+            match tile {
+                Tile::Player if player.is_some() => errors.push(LevelError::new(
+                    line_no,
+                    column,
+                    LevelErrorKind::DuplicatePlayer,
+                )),
+                Tile::Player => player = Some((x, y)),
+                Tile::Teleporter if teleporter.is_some() => errors.push(LevelError::new(
+                    line_no,
+                    column,
+                    LevelErrorKind::DuplicateTeleporter,
+                )),
+                Tile::Teleporter => teleporter = Some((x, y)),
+                Tile::Potion => potions += 1,
+                _ => (),
+            }
+
+            let on_border = x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+            if on_border && tile != Tile::Wall {
+                errors.push(LevelError::new(line_no, column, LevelErrorKind::OpenBorder));
+            }
+
+            if x < width && y < height {
+                tiles[y * width + x] = tile;
+            }
+        }
+    }
+
+    if player.is_none() {
+        errors.push(LevelError::new(map_line, 1, LevelErrorKind::MissingPlayer));
+    }
+    if teleporter.is_none() {
+        errors.push(LevelError::new(
+            map_line,
+            1,
+            LevelErrorKind::MissingTeleporter,
+        ));
+    }
+    if potions == 0 {
+        errors.push(LevelError::new(map_line, 1, LevelErrorKind::NoPotions));
+    }
+
+    level.tiles = tiles;
+}
+
+// A pack manifest lists the levels that make up a pack in play order, e.g.
+//
+//   title: The Original Ten
+//   author: Paul Cockrell
+//   ---
+//   original/level-01.level
+//   original/level-02.level
+//
+// Level paths are relative to the manifest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PackManifest {
+    pub title: String,
+    pub author: Option<String>,
+    pub levels: Vec<String>,
+}
+
+pub fn parse_pack(source: &str) -> Result<PackManifest, Vec<LevelError>> {
+    let mut errors = Vec::new();
+    let mut pack = PackManifest::default();
+
+    let lines: Vec<(usize, &str)> = source
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+
+    let level_lines = match lines.iter().position(|(_, line)| *line == HEADER_SEPARATOR) {
+        Some(separator) => {
+            for &(line_no, line) in &lines[..separator] {
+                let Some((key, value)) = line.split_once(':') else {
+                    errors.push(LevelError::new(line_no, 1, LevelErrorKind::MalformedHeader));
+                    continue;
+                };
+                match key.trim() {
+                    "title" => pack.title = value.trim().to_string(),
+                    "author" => pack.author = Some(value.trim().to_string()),
+                    unknown => errors.push(LevelError::new(
+                        line_no,
+                        1,
+                        LevelErrorKind::UnknownHeaderKey(unknown.to_string()),
+                    )),
+                }
+            }
+            &lines[separator + 1..]
+        }
+        None => &lines[..],
+    };
+
+    pack.levels = level_lines
+        .iter()
+        .map(|(_, line)| line.to_string())
+        .collect();
+
+    if pack.levels.is_empty() {
+        let line_no = lines.last().map(|(line_no, _)| *line_no).unwrap_or(1);
+        errors.push(LevelError::new(line_no, 1, LevelErrorKind::EmptyPack));
+    }
+
+    if errors.is_empty() {
+        Ok(pack)
+    } else {
+        Err(errors)
+    }
+}
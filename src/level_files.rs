@@ -0,0 +1,84 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::level_def::{parse_level, parse_pack, LevelDef, LevelError};
+use crate::levels::PACK_FILES;
+
+// Reads levels straight from disk, for the command line tools and tests. The game itself loads
+// them through the asset server instead, see `levels`.
+
+#[derive(Debug)]
+pub enum LevelFileError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, Vec<LevelError>),
+}
+
+impl fmt::Display for LevelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelFileError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            // One diagnostic per line, formatted like a compiler's so editors can jump to them
+            LevelFileError::Parse(path, errors) => {
+                for (idx, err) in errors.iter().enumerate() {
+                    if idx > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}:{err}", path.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LevelFileError {}
+
+// A level and the file it was read from
+#[derive(Clone, Debug)]
+pub struct LevelFile {
+    pub path: PathBuf,
+    pub level: LevelDef,
+}
+
+// The built in packs, in the order the game lists them
+pub fn builtin_packs(assets: &Path) -> Vec<PathBuf> {
+    PACK_FILES.iter().map(|path| assets.join(path)).collect()
+}
+
+pub fn is_pack(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "pack")
+}
+
+fn read(path: &Path) -> Result<String, LevelFileError> {
+    std::fs::read_to_string(path).map_err(|err| LevelFileError::Io(path.into(), err))
+}
+
+pub fn read_level(path: &Path) -> Result<LevelDef, LevelFileError> {
+    parse_level(&read(path)?).map_err(|errors| LevelFileError::Parse(path.into(), errors))
+}
+
+// Reads a level file, or every level of a pack in play order
+pub fn read_levels(path: &Path) -> Result<Vec<LevelFile>, LevelFileError> {
+    if !is_pack(path) {
+        let level = read_level(path)?;
+        return Ok(vec![LevelFile {
+            path: path.into(),
+            level,
+        }]);
+    }
+
+    let manifest =
+        parse_pack(&read(path)?).map_err(|errors| LevelFileError::Parse(path.into(), errors))?;
+
+    // Level paths are relative to the manifest
+    let pack_dir = path.parent().unwrap_or(Path::new(""));
+    manifest
+        .levels
+        .iter()
+        .map(|level_path| {
+            let path = pack_dir.join(level_path);
+            let level = read_level(&path)?;
+            Ok(LevelFile { path, level })
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+use crate::level_def::LevelDef;
+
+// The eight ways a level can be turned and flipped without changing how it plays. Every tile moves
+// as a whole so walls, potions, the player and the teleporter keep their meaning, and as movement
+// is the same in all four directions every transformed level is exactly as solvable as the
+// original.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LevelTransform {
+    Identity,
+    // Clockwise rotations
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // Left becomes right
+    MirrorHorizontal,
+    // Top becomes bottom
+    MirrorVertical,
+    // Mirrored along the top left to bottom right diagonal
+    MirrorDiagonal,
+    // Mirrored along the top right to bottom left diagonal
+    MirrorAntiDiagonal,
+}
+
+impl LevelTransform {
+    pub const ALL: [LevelTransform; 8] = [
+        LevelTransform::Identity,
+        LevelTransform::Rotate90,
+        LevelTransform::Rotate180,
+        LevelTransform::Rotate270,
+        LevelTransform::MirrorHorizontal,
+        LevelTransform::MirrorVertical,
+        LevelTransform::MirrorDiagonal,
+        LevelTransform::MirrorAntiDiagonal,
+    ];
+
+    // Whether the transformed level is on its side, i.e width and height are swapped
+    pub fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            LevelTransform::Rotate90
+                | LevelTransform::Rotate270
+                | LevelTransform::MirrorDiagonal
+                | LevelTransform::MirrorAntiDiagonal
+        )
+    }
+
+    // The transform that puts a transformed level back how it was
+    pub fn inverse(self) -> Self {
+        match self {
+            LevelTransform::Rotate90 => LevelTransform::Rotate270,
+            LevelTransform::Rotate270 => LevelTransform::Rotate90,
+            // Everything else undoes itself
+            transform => transform,
+        }
+    }
+
+    // Where the tile at `x, y` of a `width` by `height` grid ends up
+    pub fn apply_position(self, x: usize, y: usize, width: usize, height: usize) -> (usize, usize) {
+        let (right, bottom) = (width - 1, height - 1);
+        match self {
+            LevelTransform::Identity => (x, y),
+            LevelTransform::Rotate90 => (bottom - y, x),
+            LevelTransform::Rotate180 => (right - x, bottom - y),
+            LevelTransform::Rotate270 => (y, right - x),
+            LevelTransform::MirrorHorizontal => (right - x, y),
+            LevelTransform::MirrorVertical => (x, bottom - y),
+            LevelTransform::MirrorDiagonal => (y, x),
+            LevelTransform::MirrorAntiDiagonal => (bottom - y, right - x),
+        }
+    }
+
+    // Returns a copy of the level with its map transformed, the metadata is left untouched
+    pub fn apply(self, level: &LevelDef) -> LevelDef {
+        let (width, height) = if self.swaps_axes() {
+            (level.height, level.width)
+        } else {
+            (level.width, level.height)
+        };
+
+        let mut tiles = level.tiles.clone();
+        for y in 0..level.height {
+            for x in 0..level.width {
+                let (new_x, new_y) = self.apply_position(x, y, level.width, level.height);
+                tiles[new_y * width + new_x] = level.tile(x, y);
+            }
+        }
+
+        LevelDef {
+            width,
+            height,
+            tiles,
+            ..level.clone()
+        }
+    }
+}
+
+impl fmt::Display for LevelTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LevelTransform::Identity => "original",
+            LevelTransform::Rotate90 => "rotated 90",
+            LevelTransform::Rotate180 => "rotated 180",
+            LevelTransform::Rotate270 => "rotated 270",
+            LevelTransform::MirrorHorizontal => "mirrored",
+            LevelTransform::MirrorVertical => "flipped",
+            LevelTransform::MirrorDiagonal => "mirrored diagonally",
+            LevelTransform::MirrorAntiDiagonal => "flipped diagonally",
+        };
+        write!(f, "{name}")
+    }
+}
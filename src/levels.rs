@@ -1,132 +1,306 @@
-use bevy::prelude::*;
-
-// This is not ideal. Originally the levels were in the assets folder and were read in from there.
-// But of couse this won't work in the world of WASM, of course! So as a temporary work around, as
-// its boxing day 2023 and I should be doing something else, this is all I can think of doing right now.
-const LEVEL_1: &str = "
-############
-#.#.......##
-#.#.....o.##
-#.##..o....#
-#..#o......#
-#..#...###.#
-#.o........#
-#...p.t..o.#
-############";
-
-const LEVEL_2: &str = "
-############
-####.....p.#
-###..#o#...#
-##....#o...#
-#....#o#o..#
-#...#...#..#
-#.........##
-#t.......###
-############";
-
-const LEVEL_3: &str = "
-############
-#........#.#
-#..##...##.#
-#.o#.......#
-#.....##...#
-#.....o#.o.#
-#.#o.......#
-#.##p#t....#
-############";
-
-const LEVEL_4: &str = "
-############
-#..........#
-##...#o....#
-#...###....#
-#.#.o#..#o.#
-####p..###.#
-#.#o....#o.#
-#.....t..#.#
-############";
-
-const LEVEL_5: &str = "
-############
-#........#o#
-#....o.....#
-#..........#
-##o#.#..#.##
-#.#o#..#o#.#
-#..........#
-#..#p.t.#..#
-############";
-
-const LEVEL_6: &str = "
-############
-#.....##...#
-#......#o#.#
-#.#.#..o#..#
-#.o#o..#.#.#
-#.#.#......#
-#..........#
-#...t...p#.#
-############";
-
-const LEVEL_7: &str = "
-############
-#####......#
-####......##
-###o.#o....#
-##o.###..o##
-#....#..p###
-##......####
-#...t..#####
-############";
-
-const LEVEL_8: &str = "
-############
-#..........#
-#..#o..##..#
-#..###.o#o.#
-#....#.....#
-####.p.....#
-###o..###..#
-#.....t....#
-############";
-
-const LEVEL_9: &str = "
-############
-#..........#
-#.##o..##..#
-#.#.#.#o...#
-#.#.#.o#o..#
-#.#.#..o#..#
-#.##p.##...#
-#.....t....#
-############";
-
-const LEVEL_10: &str = "
-############
-#..........#
-#..#..##...#
-#.#.#..o#..#
-#.o#o..#o..#
-#.#.#..o#..#
-#..#..##...#
-#...t.p....#
-############";
+use std::fmt;
+
+use crate::endless::EndlessRun;
+use crate::level_def::{parse_level, parse_pack, LevelDef, LevelError};
+use crate::Level;
+use bevy::{
+    asset::{
+        io::Reader, AssetLoader, AsyncReadExt, LoadContext, LoadState, ParseAssetPathError,
+        RecursiveDependencyLoadState,
+    },
+    ecs::system::SystemParam,
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+// Levels live in `assets/levels`, one per file, see `level_def` for the format. They are grouped
+// into packs by a manifest listing the levels in play order. Everything is loaded through the
+// asset server so the same files are served to native and WASM builds alike.
+pub const PACK_FILES: [&str; 2] = ["levels/original.pack", "levels/hard.pack"];
+
+#[derive(Asset, TypePath, Debug, Deref)]
+pub struct LevelAsset(pub LevelDef);
+
+#[derive(Asset, TypePath, Debug)]
+pub struct LevelPack {
+    pub title: String,
+    pub author: Option<String>,
+    #[dependency]
+    pub levels: Vec<Handle<LevelAsset>>,
+}
+
+#[derive(Debug)]
+pub enum LevelLoaderError {
+    Io(std::io::Error),
+    Utf8(std::str::Utf8Error),
+    Parse(Vec<LevelError>),
+    Path(ParseAssetPathError),
+}
+
+impl fmt::Display for LevelLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoaderError::Io(err) => write!(f, "could not read level file: {err}"),
+            LevelLoaderError::Utf8(err) => write!(f, "level file is not valid UTF-8: {err}"),
+            LevelLoaderError::Parse(errors) => {
+                write!(f, "invalid level file")?;
+                for err in errors {
+                    write!(f, "\n  {err}")?;
+                }
+                Ok(())
+            }
+            LevelLoaderError::Path(err) => write!(f, "invalid level path in pack: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelLoaderError {}
+
+async fn read_source(reader: &mut Reader<'_>) -> Result<String, LevelLoaderError> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .await
+        .map_err(LevelLoaderError::Io)?;
+    let source = std::str::from_utf8(&bytes).map_err(LevelLoaderError::Utf8)?;
+
+    Ok(source.to_string())
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelAsset;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let source = read_source(reader).await?;
+            let level_def = parse_level(&source).map_err(LevelLoaderError::Parse)?;
+
+            Ok(LevelAsset(level_def))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level"]
+    }
+}
+
+#[derive(Default)]
+pub struct LevelPackLoader;
+
+impl AssetLoader for LevelPackLoader {
+    type Asset = LevelPack;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let source = read_source(reader).await?;
+            let manifest = parse_pack(&source).map_err(LevelLoaderError::Parse)?;
+
+            // Level paths are relative to the manifest
+            let mut levels = Vec::new();
+            for level_path in &manifest.levels {
+                let path = load_context
+                    .asset_path()
+                    .resolve_embed(level_path)
+                    .map_err(LevelLoaderError::Path)?;
+                levels.push(load_context.load(path));
+            }
+
+            Ok(LevelPack {
+                title: manifest.title,
+                author: manifest.author,
+                levels,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pack"]
+    }
+}
+
+// Sent when the level being played (or the pack it is in) changes on disk
+#[derive(Event, Debug)]
+pub struct LevelReloaded;
 
 #[derive(Resource)]
 pub struct LevelManager {
-    pub maps: [&'static str; 10],
+    pub packs: Vec<Handle<LevelPack>>,
+    // Index into `packs` of the pack being played
+    pub active: usize,
+    // A pack built while the game is running (e.g from a level code), played instead of the
+    // active pack when set
+    pub custom: Option<Handle<LevelPack>>,
 }
 
 impl LevelManager {
-    pub fn new() -> Self {
+    pub fn new(asset_server: &AssetServer) -> Self {
         Self {
-            maps: [
-                LEVEL_1, LEVEL_2, LEVEL_3, LEVEL_4, LEVEL_5, LEVEL_6, LEVEL_7, LEVEL_8, LEVEL_9,
-                LEVEL_10,
-            ],
+            packs: PACK_FILES
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+            active: 0,
+            custom: None,
         }
     }
+
+    // True once every pack, and the levels in it, has finished loading (or failed to)
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        self.packs.iter().all(|handle| {
+            matches!(asset_server.get_load_state(handle), Some(LoadState::Failed))
+                || matches!(
+                    asset_server.get_recursive_dependency_load_state(handle),
+                    Some(RecursiveDependencyLoadState::Loaded)
+                        | Some(RecursiveDependencyLoadState::Failed)
+                )
+        })
+    }
+}
+
+impl LevelManager {
+    // Takes levels that failed to load out of their packs, so only levels that can be played are
+    // offered, and makes sure the active pack has some. Done once loading has finished.
+    pub fn drop_failed_levels(
+        &mut self,
+        asset_server: &AssetServer,
+        pack_assets: &mut Assets<LevelPack>,
+    ) {
+        let failed = |level: &Handle<LevelAsset>| {
+            matches!(asset_server.get_load_state(level), Some(LoadState::Failed))
+        };
+        for handle in &self.packs {
+            // Only changed when it has to be, as changing it looks like it was edited on disk
+            if !pack_assets
+                .get(handle)
+                .is_some_and(|pack| pack.levels.iter().any(failed))
+            {
+                continue;
+            }
+            let Some(pack) = pack_assets.get_mut(handle) else {
+                continue;
+            };
+            for level in pack.levels.iter().filter(|level| failed(level)) {
+                let path = level.path().map(|path| path.to_string());
+                println!(
+                    "Leaving {} out of {:?}, it failed to load",
+                    path.unwrap_or_default(),
+                    pack.title
+                );
+            }
+            pack.levels.retain(|level| !failed(level));
+        }
+
+        let playable = |handle: &Handle<LevelPack>| {
+            pack_assets
+                .get(handle)
+                .is_some_and(|pack| !pack.levels.is_empty())
+        };
+        if !self.packs.get(self.active).is_some_and(playable) {
+            if let Some(active) = self.packs.iter().position(playable) {
+                self.active = active;
+            }
+        }
+    }
+}
+
+impl FromWorld for LevelManager {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.resource::<AssetServer>())
+    }
+}
+
+// Read access to the loaded packs and the levels in them
+#[derive(SystemParam)]
+pub struct Levels<'w> {
+    pub manager: Res<'w, LevelManager>,
+    packs: Res<'w, Assets<LevelPack>>,
+    levels: Res<'w, Assets<LevelAsset>>,
+    endless: Option<Res<'w, EndlessRun>>,
+}
+
+impl<'w> Levels<'w> {
+    // All the packs that loaded successfully with levels left to play, alongside their index in
+    // the manager
+    pub fn packs(&self) -> impl Iterator<Item = (usize, &LevelPack)> {
+        self.manager
+            .packs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, handle)| Some((idx, self.packs.get(handle)?)))
+            .filter(|(_, pack)| !pack.levels.is_empty())
+    }
+
+    pub fn active_pack(&self) -> Option<&LevelPack> {
+        self.packs.get(self.active_pack_handle()?)
+    }
+
+    // Number of levels in the active pack
+    pub fn count(&self) -> usize {
+        self.active_pack().map_or(0, |pack| pack.levels.len())
+    }
+
+    // Whether the given level is the final one of the active pack, an endless run has no final
+    // level as more are generated as it goes
+    pub fn is_last(&self, level: &Level) -> bool {
+        self.endless.is_none() && level.index + 1 >= self.count()
+    }
+
+    // Returns the definition for the given level of the active pack
+    pub fn get(&self, level: &Level) -> Option<&LevelDef> {
+        self.levels.get(self.handle(level)?).map(|level| &level.0)
+    }
+
+    pub fn handle(&self, level: &Level) -> Option<&Handle<LevelAsset>> {
+        self.active_pack()?.levels.get(level.index)
+    }
+
+    pub fn active_pack_handle(&self) -> Option<&Handle<LevelPack>> {
+        match &self.manager.custom {
+            Some(custom) => Some(custom),
+            None => self.manager.packs.get(self.manager.active),
+        }
+    }
+}
+
+// Watches for the level being played being edited (with the `dev` feature) so the
+// board can be rebuilt without restarting the game
+fn detect_level_changes(
+    mut level_events: EventReader<AssetEvent<LevelAsset>>,
+    mut pack_events: EventReader<AssetEvent<LevelPack>>,
+    mut reloaded: EventWriter<LevelReloaded>,
+    level: Res<Level>,
+    levels: Levels,
+) {
+    let level_modified = level_events.read().any(|event| match event {
+        AssetEvent::Modified { id } => levels.handle(&level).is_some_and(|h| h.id() == *id),
+        _ => false,
+    });
+    let pack_modified = pack_events.read().any(|event| match event {
+        AssetEvent::Modified { id } => levels.active_pack_handle().is_some_and(|h| h.id() == *id),
+        _ => false,
+    });
+
+    if level_modified || pack_modified {
+        println!("Level {} changed on disk, reloading", level.number());
+        reloaded.send(LevelReloaded);
+    }
 }
 
 #[derive(Component, Debug)]
@@ -134,6 +308,12 @@ pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(LevelManager::new());
+        app.init_asset::<LevelAsset>()
+            .init_asset::<LevelPack>()
+            .init_asset_loader::<LevelLoader>()
+            .init_asset_loader::<LevelPackLoader>()
+            .init_resource::<LevelManager>()
+            .add_event::<LevelReloaded>()
+            .add_systems(Update, detect_level_changes);
     }
 }
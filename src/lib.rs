@@ -0,0 +1,75 @@
+// Bevy systems naturally take many, verbose, parameters
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+pub mod ascii;
+pub mod audio;
+pub mod button;
+pub mod camera;
+pub mod clipboard;
+pub mod daily;
+pub mod endless;
+pub mod game;
+pub mod generator;
+pub mod hint;
+pub mod hud;
+pub mod level_code;
+pub mod level_def;
+pub mod level_files;
+pub mod level_transform;
+pub mod levels;
+pub mod map;
+pub mod menu;
+pub mod movement;
+pub mod playback;
+pub mod preview;
+pub mod rng;
+pub mod sim;
+pub mod solver;
+pub mod splash;
+pub mod summary;
+pub mod text_input;
+pub mod thumbnail;
+pub mod view_port;
+
+pub const TEXT_COLOR: Color = Color::rgb(0.9, 0.8, 0.7);
+
+// Time given each frame to work spread over several, like generating and solving levels, small
+// enough not to drop frames
+pub const FRAME_BUDGET: Duration = Duration::from_millis(4);
+
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum GameState {
+    #[default]
+    Splash,
+    Menu,
+    GameSetup,
+    GamePlay,
+    // Showing how the level just finished went
+    LevelCompleted,
+    GameCompleted,
+}
+
+// The level being played, as an index into the active pack
+#[derive(Resource, Debug, Component, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Level {
+    index: usize,
+}
+
+impl Level {
+    // Level number as shown to the player, starting at 1
+    pub fn number(&self) -> usize {
+        self.index + 1
+    }
+}
+
+// Generic system that takes a component as a parameter, and will despawn all entites with that
+// component
+pub fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
+    }
+}
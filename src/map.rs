@@ -1,8 +1,16 @@
-use crate::levels::LevelManager;
+use crate::level_def::Tile;
+use crate::levels::Levels;
 use crate::movement::Moveable;
-use crate::prelude::*;
+use crate::sim::{Board, Cell};
 use crate::{ascii::*, Level};
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
+
+// Sprite sheet indices for each kind of tile
+pub const WALL_SPRITE: usize = 40;
+pub const FLOOR_SPRITE: usize = 48;
+pub const POTION_SPRITE: usize = 115;
+pub const PLAYER_SPRITE: usize = 84;
+pub const TELEPORTER_SPRITE: usize = 60;
 
 #[derive(Component, Debug)]
 pub struct TileCollider;
@@ -22,20 +30,118 @@ pub enum TileType {
     Teleport,
 }
 
-pub struct Map {
-    tiles: Vec<TileType>,
-}
-
 #[derive(Component, Debug)]
 pub struct TileMap;
 
+// The cell an interactive sprite (the player, teleporter or a potion) is in on the board
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridPosition(pub Cell);
+
+// The state of the level being played, the sprites on screen are a view of this
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct BoardState(pub Board);
+
+// Which entities are in each cell of the board, so finding what is at a cell doesn't mean going
+// through every entity. Kept in step with `GridPosition` by `sync_occupancy`.
+#[derive(Resource, Debug, Default)]
+pub struct Occupancy {
+    width: usize,
+    height: usize,
+    // Row major, one list of entities per cell
+    cells: Vec<Vec<Entity>>,
+    // The cell each entity was last seen in, to find it again when it moves or is despawned
+    positions: HashMap<Entity, Cell>,
+}
+
+impl Occupancy {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Vec::new(); width * height],
+            positions: HashMap::default(),
+        }
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        (cell.x < self.width && cell.y < self.height).then_some(cell.y * self.width + cell.x)
+    }
+
+    // Entities in the given cell, empty for cells off the board
+    pub fn get(&self, cell: Cell) -> &[Entity] {
+        self.index(cell).map_or(&[], |idx| &self.cells[idx])
+    }
+
+    pub fn insert(&mut self, entity: Entity, cell: Cell) {
+        self.remove(entity);
+        if let Some(idx) = self.index(cell) {
+            self.cells[idx].push(entity);
+            self.positions.insert(entity, cell);
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(cell) = self.positions.remove(&entity) else {
+            return;
+        };
+        if let Some(idx) = self.index(cell) {
+            self.cells[idx].retain(|other| *other != entity);
+        }
+    }
+}
+
+// Updates the occupancy grid with the entities that have been spawned, moved or despawned since
+// it last ran
+pub fn sync_occupancy(
+    mut occupancy: ResMut<Occupancy>,
+    moved_query: Query<(Entity, &GridPosition), Changed<GridPosition>>,
+    mut removed: RemovedComponents<GridPosition>,
+) {
+    for entity in removed.read() {
+        occupancy.remove(entity);
+    }
+    for (entity, position) in &moved_query {
+        occupancy.insert(entity, position.0);
+    }
+}
+
+// Dimensions of the current level in tiles, the board is centred on the world origin
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl MapSize {
+    // World position of the centre of the tile at the given column and row
+    pub fn translation(&self, x: usize, y: usize, z: f32) -> Vec3 {
+        let origin_x = -((self.width as f32 - 1.0) * TILE_SIZE) / 2.0;
+        let origin_y = ((self.height as f32 - 1.0) * TILE_SIZE) / 2.0;
+
+        Vec3::new(
+            origin_x + x as f32 * TILE_SIZE,
+            origin_y - y as f32 * TILE_SIZE,
+            z,
+        )
+    }
+
+    // Size of the board in world units
+    pub fn world_size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32) * TILE_SIZE
+    }
+}
+
 #[derive(Component, Debug)]
 pub struct AssetMap;
 
-impl Map {
-    pub fn new(num_tiles: usize) -> Self {
-        Self {
-            tiles: vec![TileType::Floor; num_tiles],
+impl From<Tile> for TileType {
+    fn from(tile: Tile) -> Self {
+        match tile {
+            Tile::Wall => TileType::Wall,
+            Tile::Floor => TileType::Floor,
+            Tile::Potion => TileType::Potion,
+            Tile::Player => TileType::Player,
+            Tile::Teleporter => TileType::Teleport,
         }
     }
 }
@@ -45,49 +151,43 @@ pub fn spawn_map(
     mut commands: Commands,
     ascii: Res<AsciiSheet>,
     level: Res<Level>,
-    level_manager: Res<LevelManager>,
+    levels: Levels,
 ) {
-    println!("Spawning map for level {}", level.number);
+    println!("Spawning map for level {}", level.number());
 
-    let level_map = level_manager.maps[(level.number - 1) as usize];
-    let mut map = Map::new(MAP_NUM_TILES as usize);
+    let Some(level_def) = levels.get(&level) else {
+        println!("Level {} has not been loaded", level.number());
+        return;
+    };
+    let map_size = MapSize {
+        width: level_def.width,
+        height: level_def.height,
+    };
     let mut tiles = Vec::new();
 
-    // Build static
-    for (y, line) in level_map.lines().filter(|s| !s.is_empty()).enumerate() {
-        for (x, char) in line.chars().enumerate() {
-            let idx = map_idx(x as i32, y as i32);
-            match char {
-                '#' => map.tiles[idx] = TileType::Wall,
-                _ => map.tiles[idx] = TileType::Floor,
-            }
-        }
-    }
-
-    for y in 0..MAP_NUM_TILES_HIGH {
-        for x in 0..MAP_NUM_TILES_WIDE {
-            let index = map_idx(x, y);
-            let tile_type = map.tiles[index];
+    for y in 0..map_size.height {
+        for x in 0..map_size.width {
+            // Only walls are static, everything else sits on a floor tile
+            let tile_type = match level_def.tile(x, y) {
+                Tile::Wall => TileType::Wall,
+                _ => TileType::Floor,
+            };
             let (sprite_idx, z_idx) = match tile_type {
-                TileType::Wall => (40, 1.0),
-                _ => (48, 0.0),
+                TileType::Wall => (WALL_SPRITE, 1.0),
+                _ => (FLOOR_SPRITE, 0.0),
             };
 
             let sprite = spawn_ascii_sprite(
                 &mut commands,
                 &ascii,
                 sprite_idx,
-                Vec3::new(
-                    -90.0 + (x as f32 * TILE_SIZE),
-                    65.0 + -(y as f32 * TILE_SIZE),
-                    z_idx,
-                ),
+                map_size.translation(x, y, z_idx),
             );
 
             if matches!(tile_type, TileType::Wall) {
                 commands
                     .entity(sprite)
-                    .insert((tile_type, TileCollider))
+                    .insert((tile_type, TileCollider, GridPosition(Cell::new(x, y))))
                     .insert(TileType::Wall);
             } else {
                 commands.entity(sprite).insert(tile_type);
@@ -110,62 +210,84 @@ pub fn spawn_map(
         .insert(GlobalTransform::default())
         .push_children(&tiles);
 
+    commands.insert_resource(map_size);
+    commands.insert_resource(Occupancy::new(map_size.width, map_size.height));
+
     println!("Spawn map done");
 }
 
+// Spawns the sprite for a potion that is still to be picked up, it needs adding to the `AssetMap`
+pub fn spawn_potion(
+    commands: &mut Commands,
+    ascii: &AsciiSheet,
+    map_size: &MapSize,
+    cell: Cell,
+) -> Entity {
+    let sprite = spawn_ascii_sprite(
+        commands,
+        ascii,
+        POTION_SPRITE,
+        map_size.translation(cell.x, cell.y, 1.0),
+    );
+    commands
+        .entity(sprite)
+        .insert((TileType::Potion, Collectable, GridPosition(cell)));
+
+    sprite
+}
+
 // Builds the assets, i.e Player and Potions. Done seperately to the static map assets, as they
 // need to be 'behind' these ones
 pub fn spawn_assets(
     mut commands: Commands,
     ascii: Res<AsciiSheet>,
     level: Res<Level>,
-    level_manager: Res<LevelManager>,
+    levels: Levels,
 ) {
-    println!("Spawning assets for level {}", level.number);
+    println!("Spawning assets for level {}", level.number());
 
-    let level_map = level_manager.maps[(level.number - 1) as usize];
-    let mut map = Map::new(MAP_NUM_TILES as usize);
+    let Some(level_def) = levels.get(&level) else {
+        println!("Level {} has not been loaded", level.number());
+        return;
+    };
+    let map_size = MapSize {
+        width: level_def.width,
+        height: level_def.height,
+    };
     let mut sprites = Vec::new();
 
-    // Build static
-    for (y, line) in level_map.lines().filter(|s| !s.is_empty()).enumerate() {
-        for (x, char) in line.chars().enumerate() {
-            let idx = map_idx(x as i32, y as i32);
-            match char {
-                'o' => map.tiles[idx] = TileType::Potion,
-                'p' => map.tiles[idx] = TileType::Player,
-                't' => map.tiles[idx] = TileType::Teleport,
-                _ => (),
+    for y in 0..map_size.height {
+        for x in 0..map_size.width {
+            let tile_type = TileType::from(level_def.tile(x, y));
+            if let TileType::Potion = tile_type {
+                sprites.push(spawn_potion(
+                    &mut commands,
+                    &ascii,
+                    &map_size,
+                    Cell::new(x, y),
+                ));
+                continue;
             }
-        }
-    }
 
-    for y in 0..MAP_NUM_TILES_HIGH {
-        for x in 0..MAP_NUM_TILES_WIDE {
-            let index = map_idx(x, y);
-            let tile_type = map.tiles[index];
             if let Some((sprite_idx, z_idx)) = match tile_type {
-                TileType::Potion => Some((115, 1.0)),
-                TileType::Player => Some((84, 2.0)),
-                TileType::Teleport => Some((60, 1.0)),
+                TileType::Player => Some((PLAYER_SPRITE, 2.0)),
+                TileType::Teleport => Some((TELEPORTER_SPRITE, 1.0)),
                 _ => None,
             } {
                 let sprite = spawn_ascii_sprite(
                     &mut commands,
                     &ascii,
                     sprite_idx,
-                    Vec3::new(
-                        -90.0 + (x as f32 * TILE_SIZE),
-                        65.0 + -(y as f32 * TILE_SIZE),
-                        z_idx,
-                    ),
+                    map_size.translation(x, y, z_idx),
                 );
 
+                commands
+                    .entity(sprite)
+                    .insert(GridPosition(Cell::new(x, y)));
                 match tile_type {
-                    TileType::Player => {
-                        commands.entity(sprite).insert((tile_type, Moveable::new()))
-                    }
-                    TileType::Potion => commands.entity(sprite).insert((tile_type, Collectable)),
+                    TileType::Player => commands
+                        .entity(sprite)
+                        .insert((tile_type, Moveable::new(Cell::new(x, y)))),
                     TileType::Teleport => {
                         commands
                             .entity(sprite)
@@ -192,9 +314,7 @@ pub fn spawn_assets(
         .insert(GlobalTransform::default())
         .push_children(&sprites);
 
-    println!("Spawn assets done");
-}
+    commands.insert_resource(BoardState(Board::new(level_def)));
 
-pub fn map_idx(x: i32, y: i32) -> usize {
-    ((y * MAP_NUM_TILES_WIDE) + x) as usize
+    println!("Spawn assets done");
 }
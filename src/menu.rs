@@ -1,6 +1,16 @@
 use bevy::{app::AppExit, prelude::*};
 
-use crate::button::{button_style, button_system, button_text_style, NORMAL_BUTTON};
+use crate::button::{
+    button_style, button_system, button_text_style, SelectedOption, NORMAL_BUTTON, PRESSED_BUTTON,
+};
+use crate::daily::{DailyPuzzle, Date, PendingDaily};
+use crate::endless::{EndlessRun, PendingLevel};
+use crate::level_code;
+use crate::level_transform::LevelTransform;
+use crate::levels::{LevelAsset, LevelManager, LevelPack, Levels};
+use crate::preview::GhostPreview;
+use crate::rng::Rng;
+use crate::text_input::{text_input_style, text_input_system, TextInput};
 
 use super::{despawn_screen, GameState, TEXT_COLOR};
 
@@ -15,7 +25,48 @@ impl Plugin for MenuPlugin {
                 (menu_action, button_system).run_if(in_state(GameState::Menu)),
             )
             .add_systems(OnEnter(MenuState::Main), main_menu_setup)
-            .add_systems(OnExit(MenuState::Main), despawn_screen::<OnMainMenuScreen>)
+            .add_systems(
+                Update,
+                (remix, start_daily, wait_for_daily).run_if(in_state(MenuState::Main)),
+            )
+            .add_systems(
+                OnExit(MenuState::Main),
+                (despawn_screen::<OnMainMenuScreen>, cancel_daily),
+            )
+            .add_systems(OnEnter(MenuState::Endless), endless_screen_setup)
+            .add_systems(
+                OnExit(MenuState::Endless),
+                (despawn_screen::<OnEndlessScreen>, cancel_endless),
+            )
+            .add_systems(
+                Update,
+                (text_input_system, start_endless, wait_for_endless)
+                    .chain()
+                    .run_if(in_state(MenuState::Endless)),
+            )
+            .add_systems(OnEnter(MenuState::Packs), packs_screen_setup)
+            .add_systems(OnExit(MenuState::Packs), despawn_screen::<OnPacksScreen>)
+            .add_systems(Update, pack_select.run_if(in_state(MenuState::Packs)))
+            .add_systems(OnEnter(MenuState::ImportCode), import_code_screen_setup)
+            .add_systems(
+                OnExit(MenuState::ImportCode),
+                despawn_screen::<OnImportCodeScreen>,
+            )
+            .add_systems(
+                Update,
+                (text_input_system, import_code)
+                    .chain()
+                    .run_if(in_state(MenuState::ImportCode)),
+            )
+            .add_systems(OnEnter(MenuState::Settings), settings_screen_setup)
+            .add_systems(
+                OnExit(MenuState::Settings),
+                despawn_screen::<OnSettingsScreen>,
+            )
+            .add_systems(
+                Update,
+                setting_button::<GhostPreview>.run_if(in_state(MenuState::Settings)),
+            )
             .add_systems(OnEnter(MenuState::Credits), credits_screen_setup)
             .add_systems(
                 OnExit(MenuState::Credits),
@@ -27,6 +78,10 @@ impl Plugin for MenuPlugin {
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 pub enum MenuState {
     Main,
+    Endless,
+    Packs,
+    ImportCode,
+    Settings,
     Credits,
     #[default]
     Disabled,
@@ -36,14 +91,40 @@ pub enum MenuState {
 struct OnMainMenuScreen;
 
 #[derive(Component)]
-struct OnCreditsScreen;
+struct OnEndlessScreen;
+
+// Tag component for the text explaining why an endless run could not be started
+#[derive(Component)]
+struct EndlessStatus;
 
 #[derive(Component)]
-struct OnGameScreen;
+struct OnPacksScreen;
+
+#[derive(Component)]
+struct OnImportCodeScreen;
+
+// Tag component for the text explaining why a level code could not be imported
+#[derive(Component)]
+struct ImportCodeStatus;
+
+#[derive(Component)]
+struct OnSettingsScreen;
+
+#[derive(Component)]
+struct OnCreditsScreen;
 
 #[derive(Component)]
 pub enum MenuButtonAction {
     Play,
+    Remix,
+    Daily,
+    Endless,
+    PlayEndless,
+    Packs,
+    SelectPack(usize),
+    ImportCode,
+    PlayCode,
+    Settings,
     Credits,
     BackToMainMenu,
     Quit,
@@ -97,15 +178,580 @@ fn main_menu_setup(mut commands: Commands) {
                         .with_text_alignment(TextAlignment::Center),
                     );
 
-                    // Display three buttons for each action available from the main menu:
+                    // Display a button for each action available from the main menu:
                     // - new game
+                    // - remix
+                    // - daily
+                    // - endless
+                    // - level packs
+                    // - import code
                     // - settings
                     // - credits
                     // - quit
+                    // There are too many to stack in one column, so they are laid out in rows of
+                    // three
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_wrap: FlexWrap::Wrap,
+                                justify_content: JustifyContent::Center,
+                                width: Val::Px(600.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (action, text) in [
+                                (MenuButtonAction::Play, "New Game"),
+                                (MenuButtonAction::Remix, "Remix"),
+                                (MenuButtonAction::Daily, "Daily"),
+                                (MenuButtonAction::Endless, "Endless"),
+                                (MenuButtonAction::Packs, "Level Packs"),
+                                (MenuButtonAction::ImportCode, "Import Code"),
+                                (MenuButtonAction::Settings, "Settings"),
+                                (MenuButtonAction::Credits, "Credits"),
+                                (MenuButtonAction::Quit, "Quit"),
+                            ] {
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: Style {
+                                                width: Val::Px(180.0),
+                                                ..button_style()
+                                            },
+                                            background_color: NORMAL_BUTTON.into(),
+                                            ..default()
+                                        },
+                                        action,
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            text,
+                                            button_text_style(),
+                                        ));
+                                    });
+                            }
+                        });
+                });
+        });
+}
+
+// Plays the active pack with every level randomly turned or flipped, the transforms are picked
+// afresh each time so a remix is rarely the same twice
+fn remix(
+    interaction_query: Query<(&Interaction, &MenuButtonAction), Changed<Interaction>>,
+    time: Res<Time<Real>>,
+    mut level_assets: ResMut<Assets<LevelAsset>>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let remix_pressed = interaction_query.iter().any(|(interaction, action)| {
+        *interaction == Interaction::Pressed && matches!(action, MenuButtonAction::Remix)
+    });
+    if !remix_pressed {
+        return;
+    }
+
+    let Some(pack) = level_manager
+        .packs
+        .get(level_manager.active)
+        .and_then(|handle| pack_assets.get(handle))
+    else {
+        return;
+    };
+
+    // Every transform but the identity, so each level looks different to the original
+    let transforms = &LevelTransform::ALL[1..];
+    let mut rng = Rng::new(time.elapsed().as_nanos() as u64);
+
+    let mut remixed = Vec::new();
+    for (idx, handle) in pack.levels.iter().enumerate() {
+        let (Some(level_asset), Some(transform)) =
+            (level_assets.get(handle), rng.choose(transforms))
+        else {
+            continue;
+        };
+        let mut level_def = transform.apply(level_asset);
+        // Levels without a name are known by their number
+        if level_def.name.is_empty() {
+            level_def.name = format!("Level {}", idx + 1);
+        }
+        level_def.name = format!("{} ({})", level_def.name, transform);
+        remixed.push(level_def);
+    }
+    println!("Remixing {} levels of {:?}", remixed.len(), pack.title);
+
+    let remix = LevelPack {
+        title: format!("{} (Remix)", pack.title),
+        author: pack.author.clone(),
+        levels: remixed
+            .into_iter()
+            .map(|level_def| level_assets.add(LevelAsset(level_def)))
+            .collect(),
+    };
+    level_manager.custom = Some(pack_assets.add(remix));
+    game_state.set(GameState::GameSetup);
+    menu_state.set(MenuState::Disabled);
+}
+
+// Starts generating today's daily puzzle, a level of its own generated from the date
+fn start_daily(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &MenuButtonAction), Changed<Interaction>>,
+    pending: Option<Res<PendingDaily>>,
+) {
+    let daily_pressed = interaction_query.iter().any(|(interaction, action)| {
+        *interaction == Interaction::Pressed && matches!(action, MenuButtonAction::Daily)
+    });
+    if !daily_pressed || pending.is_some() {
+        return;
+    }
+
+    let date = Date::today();
+    println!("Generating the daily puzzle for {date}");
+    commands.insert_resource(PendingDaily::new(date));
+}
+
+// Shows the daily puzzle is being generated on its button, and plays it once it is ready
+fn wait_for_daily(
+    daily: Option<Res<DailyPuzzle>>,
+    pending: Option<Res<PendingDaily>>,
+    button_query: Query<(&MenuButtonAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let label = if pending.is_some() {
+        "Generating..."
+    } else {
+        "Daily"
+    };
+    for (action, children) in &button_query {
+        if !matches!(action, MenuButtonAction::Daily) {
+            continue;
+        }
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != label {
+                text.sections[0].value = label.into();
+            }
+        }
+    }
+
+    if let (Some(daily), None) = (daily, pending) {
+        println!("Playing the daily puzzle for {}", daily.date);
+        game_state.set(GameState::GameSetup);
+        menu_state.set(MenuState::Disabled);
+    }
+}
+
+// Leaving the main menu before the daily puzzle was ready gives up on it
+fn cancel_daily(mut commands: Commands) {
+    commands.remove_resource::<PendingDaily>();
+}
+
+fn endless_screen_setup(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnEndlessScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        max_width: Val::Vw(90.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Endless",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Generated levels that keep getting harder. Type the seed of a run to \
+                             play it again, or leave it empty for a new one, then press Enter",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        })
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+                    parent.spawn((
+                        TextBundle::from_section("_", text_input_style())
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            })
+                            .with_background_color(Color::rgb(0.15, 0.15, 0.15)),
+                        TextInput::new(|c| c.is_ascii_digit()),
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: Color::rgb(0.9, 0.3, 0.3),
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                        EndlessStatus,
+                    ));
+
+                    for (action, text) in [
+                        (MenuButtonAction::PlayEndless, "Play"),
+                        (MenuButtonAction::BackToMainMenu, "Back"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style(),
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(text, button_text_style()));
+                            });
+                    }
+                });
+        });
+}
+
+// Starts an endless run from the typed seed when Play (or Enter) is pressed, or from a random one
+// when nothing was typed. The first level is generated a little each frame like the rest, and the
+// run begins once it is ready.
+fn start_endless(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &MenuButtonAction), Changed<Interaction>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time<Real>>,
+    input_query: Query<&TextInput>,
+    mut status_query: Query<&mut Text, With<EndlessStatus>>,
+    pending: Option<Res<PendingLevel>>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+) {
+    let play_pressed = interaction_query.iter().any(|(interaction, action)| {
+        *interaction == Interaction::Pressed && matches!(action, MenuButtonAction::PlayEndless)
+    });
+    if !play_pressed && !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    // Already starting one
+    if pending.is_some() {
+        return;
+    }
+
+    let Ok(input) = input_query.get_single() else {
+        return;
+    };
+
+    // Random seeds are kept short so they are easy to share
+    let seed = if input.value.is_empty() {
+        Rng::new(time.elapsed().as_nanos() as u64).below(1_000_000) as u64
+    } else if let Ok(seed) = input.value.parse() {
+        seed
+    } else {
+        for mut status in &mut status_query {
+            status.sections[0].value = "That seed is too big".into();
+        }
+        return;
+    };
+
+    println!("Starting endless run with seed {seed}");
+    let run = EndlessRun::new(seed);
+    let pack = LevelPack {
+        title: format!("Endless (seed {seed})"),
+        author: None,
+        levels: Vec::new(),
+    };
+    level_manager.custom = Some(pack_assets.add(pack));
+    commands.insert_resource(PendingLevel::new(&run, 1));
+    commands.insert_resource(run);
+    for mut status in &mut status_query {
+        status.sections[0].value = "Generating...".into();
+    }
+}
+
+// Whether the first level of a run being started has been generated
+fn endless_ready(level_manager: &LevelManager, pack_assets: &Assets<LevelPack>) -> bool {
+    level_manager
+        .custom
+        .as_ref()
+        .and_then(|handle| pack_assets.get(handle))
+        .is_some_and(|pack| !pack.levels.is_empty())
+}
+
+// Plays the first level of a run once it has been generated
+fn wait_for_endless(
+    mut commands: Commands,
+    run: Option<Res<EndlessRun>>,
+    pending: Option<Res<PendingLevel>>,
+    mut status_query: Query<&mut Text, With<EndlessStatus>>,
+    pack_assets: Res<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if run.is_none() || pending.is_some() {
+        return;
+    }
+
+    if endless_ready(&level_manager, &pack_assets) {
+        game_state.set(GameState::GameSetup);
+        menu_state.set(MenuState::Disabled);
+    } else {
+        commands.remove_resource::<EndlessRun>();
+        level_manager.custom = None;
+        for mut status in &mut status_query {
+            status.sections[0].value = "Could not generate a level from that seed".into();
+        }
+    }
+}
+
+// Leaving the endless screen before the first level was ready gives up on the run
+fn cancel_endless(
+    mut commands: Commands,
+    run: Option<Res<EndlessRun>>,
+    pack_assets: Res<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+) {
+    if run.is_some() && !endless_ready(&level_manager, &pack_assets) {
+        commands.remove_resource::<EndlessRun>();
+        commands.remove_resource::<PendingLevel>();
+        level_manager.custom = None;
+    }
+}
+
+fn packs_screen_setup(mut commands: Commands, levels: Levels) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnPacksScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Level Packs",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+
+                    // Display a button per pack, highlighting the one that will be played
+                    for (idx, pack) in levels.packs() {
+                        let mut text = format!("{} ({} levels)", pack.title, pack.levels.len());
+                        if let Some(author) = &pack.author {
+                            text = format!("{}\nby {}", text, author);
+                        }
+
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::SelectPack(idx),
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(
+                                TextBundle::from_section(text, button_text_style())
+                                    .with_text_alignment(TextAlignment::Center),
+                            );
+                        });
+                        if idx == levels.manager.active {
+                            entity.insert((SelectedOption, BackgroundColor(PRESSED_BUTTON)));
+                        }
+                    }
+
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::BackToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Back", button_text_style()));
+                        });
+                });
+        });
+}
+
+// Moves the selection highlight to the pack that was clicked, and makes it the active pack
+fn pack_select(
+    interaction_query: Query<(&Interaction, &MenuButtonAction, Entity), Changed<Interaction>>,
+    mut selected_query: Query<(Entity, &mut BackgroundColor), With<SelectedOption>>,
+    mut commands: Commands,
+    mut level_manager: ResMut<LevelManager>,
+) {
+    for (interaction, menu_button_action, entity) in &interaction_query {
+        let MenuButtonAction::SelectPack(idx) = menu_button_action else {
+            continue;
+        };
+
+        if *interaction == Interaction::Pressed && level_manager.active != *idx {
+            for (previous, mut previous_color) in &mut selected_query {
+                *previous_color = NORMAL_BUTTON.into();
+                commands.entity(previous).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            level_manager.active = *idx;
+        }
+    }
+}
+
+fn import_code_screen_setup(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnImportCodeScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        max_width: Val::Vw(90.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Import Level Code",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Type or paste (Ctrl+V) a level code, then press Enter",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+                    // Level codes are base64url
+                    parent.spawn((
+                        TextBundle::from_section("_", text_input_style())
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(10.0)),
+                                ..default()
+                            })
+                            .with_background_color(Color::rgb(0.15, 0.15, 0.15)),
+                        TextInput::new(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: Color::rgb(0.9, 0.3, 0.3),
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                        ImportCodeStatus,
+                    ));
+
                     for (action, text) in [
-                        (MenuButtonAction::Play, "New Game"),
-                        (MenuButtonAction::Credits, "Credits"),
-                        (MenuButtonAction::Quit, "Quit"),
+                        (MenuButtonAction::PlayCode, "Play"),
+                        (MenuButtonAction::BackToMainMenu, "Back"),
                     ] {
                         parent
                             .spawn((
@@ -124,6 +770,170 @@ fn main_menu_setup(mut commands: Commands) {
         });
 }
 
+// Decodes the typed level code when Play (or Enter) is pressed, and plays it as a pack of its own
+fn import_code(
+    interaction_query: Query<(&Interaction, &MenuButtonAction), Changed<Interaction>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    input_query: Query<&TextInput>,
+    mut status_query: Query<&mut Text, With<ImportCodeStatus>>,
+    mut level_assets: ResMut<Assets<LevelAsset>>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
+    mut level_manager: ResMut<LevelManager>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let play_pressed = interaction_query.iter().any(|(interaction, action)| {
+        *interaction == Interaction::Pressed && matches!(action, MenuButtonAction::PlayCode)
+    });
+    if !play_pressed && !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let Ok(input) = input_query.get_single() else {
+        return;
+    };
+
+    match level_code::decode(&input.value) {
+        Ok(level_def) => {
+            println!("Imported level {:?}", level_def.name);
+            let pack = LevelPack {
+                title: "Imported level".into(),
+                author: level_def.author.clone(),
+                levels: vec![level_assets.add(LevelAsset(level_def))],
+            };
+            level_manager.custom = Some(pack_assets.add(pack));
+            game_state.set(GameState::GameSetup);
+            menu_state.set(MenuState::Disabled);
+        }
+        Err(err) => {
+            println!("Could not import level code: {err}");
+            for mut status in &mut status_query {
+                // Only the summary line, the full diagnostics are too long to show
+                status.sections[0].value = err.to_string().lines().next().unwrap_or("").into();
+            }
+        }
+    }
+}
+
+fn settings_screen_setup(mut commands: Commands, ghost_preview: Res<GhostPreview>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnSettingsScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        max_width: Val::Vw(90.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Settings",
+                            TextStyle {
+                                font_size: 40.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Assist: ghost preview\nShows where each move would take the wizard, \
+                             and the potions he would pick up on the way",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        })
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+
+                    // Display a button per option, highlighting the one in use
+                    parent.spawn(NodeBundle::default()).with_children(|parent| {
+                        for (setting, text) in
+                            [(GhostPreview(false), "Off"), (GhostPreview(true), "On")]
+                        {
+                            let mut entity = parent.spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(120.0),
+                                        ..button_style()
+                                    },
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                },
+                                setting,
+                            ));
+                            entity.with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(text, button_text_style()));
+                            });
+                            if *ghost_preview == setting {
+                                entity.insert((SelectedOption, BackgroundColor(PRESSED_BUTTON)));
+                            }
+                        }
+                    });
+
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::BackToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Back", button_text_style()));
+                        });
+                });
+        });
+}
+
+// Moves the selection highlight to the option that was clicked, and changes the setting to it.
+// Options are buttons marked with the value they set, so one system serves every setting.
+fn setting_button<T: Resource + Component + PartialEq + Copy>(
+    interaction_query: Query<(&Interaction, &T, Entity), (Changed<Interaction>, With<Button>)>,
+    mut selected_query: Query<(Entity, &mut BackgroundColor), (With<SelectedOption>, With<T>)>,
+    mut commands: Commands,
+    mut setting: ResMut<T>,
+) {
+    for (interaction, button_setting, entity) in &interaction_query {
+        if *interaction == Interaction::Pressed && *setting != *button_setting {
+            for (previous, mut previous_color) in &mut selected_query {
+                *previous_color = NORMAL_BUTTON.into();
+                commands.entity(previous).remove::<SelectedOption>();
+            }
+            commands.entity(entity).insert(SelectedOption);
+            *setting = *button_setting;
+        }
+    }
+}
+
 fn credits_screen_setup(mut commands: Commands) {
     let button_style = Style {
         width: Val::Px(200.0),
@@ -280,6 +1090,7 @@ fn menu_action(
         (Changed<Interaction>, With<Button>),
     >,
     mut app_exit_events: EventWriter<AppExit>,
+    levels: Levels,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
 ) {
@@ -287,10 +1098,23 @@ fn menu_action(
         if *interaction == Interaction::Pressed {
             match menu_button_action {
                 MenuButtonAction::Quit => app_exit_events.send(AppExit),
+                // Every level failing to load leaves nothing to play
+                MenuButtonAction::Play if levels.count() == 0 => {
+                    println!("No levels loaded to play");
+                }
                 MenuButtonAction::Play => {
                     game_state.set(GameState::GameSetup);
                     menu_state.set(MenuState::Disabled);
                 }
+                MenuButtonAction::Packs => menu_state.set(MenuState::Packs),
+                MenuButtonAction::Endless => menu_state.set(MenuState::Endless),
+                MenuButtonAction::Remix
+                | MenuButtonAction::Daily
+                | MenuButtonAction::PlayEndless
+                | MenuButtonAction::SelectPack(_)
+                | MenuButtonAction::PlayCode => (),
+                MenuButtonAction::ImportCode => menu_state.set(MenuState::ImportCode),
+                MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
                 MenuButtonAction::Credits => menu_state.set(MenuState::Credits),
                 MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
             }
@@ -1,38 +1,45 @@
-use crate::ascii::TILE_SIZE;
-use crate::map::Collectable;
-use crate::map::Teleporter;
+use crate::ascii::{AsciiSheet, TILE_SIZE};
+use crate::levels::LevelReloaded;
+use crate::map::{
+    spawn_potion, sync_occupancy, AssetMap, BoardState, Collectable, GridPosition, MapSize,
+    Occupancy, Teleporter,
+};
+use crate::playback::SolutionPlayback;
+use crate::sim::{Board, Cell, Direction, Move};
 use crate::GameState;
-use crate::Level;
 use bevy::prelude::*;
-use bevy::sprite::collide_aabb::collide;
-use bevy::sprite::collide_aabb::Collision;
-
-use crate::map::TileCollider;
+use std::collections::VecDeque;
 
 const PLAYER_SPEED: f32 = 100.0;
 
-#[derive(Debug)]
-enum Direction {
-    Stopped,
-    Up,
-    Down,
-    Left,
-    Right,
-}
+// Gameplay runs at a fixed rate, independent of how fast frames are drawn
+const TICK_RATE: f64 = 60.0;
 
+// The wizard moves from cell to cell, the sprite is drawn part way between the cell he is leaving
+// and the next one. Which cells he passes through is worked out up front, so the frame rate only
+// changes how smoothly he gets there, never where he ends up or what he picks up.
 #[derive(Component, Debug)]
 pub struct Moveable {
-    speed: f32,
-    direction: Direction,
+    // The cell being left, or stood on when stopped
+    cell: Cell,
+    // Cells still to be entered on the slide in progress
+    path: VecDeque<Cell>,
+    // How far he is from `cell` to the front of `path`, from 0 to 1
+    progress: f32,
 }
 
 impl Moveable {
-    pub fn new() -> Self {
+    pub fn new(cell: Cell) -> Self {
         Self {
-            speed: 0.0,
-            direction: Direction::Stopped,
+            cell,
+            path: VecDeque::new(),
+            progress: 0.0,
         }
     }
+
+    pub fn is_moving(&self) -> bool {
+        !self.path.is_empty()
+    }
 }
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
@@ -44,199 +51,347 @@ pub enum PlayerState {
     CollectPotion,
 }
 
+// Sent as the wizard reaches each cell of a slide
+#[derive(Event, Debug)]
+pub struct CellEntered(pub Cell);
+
+// Moves waiting to be played, in order. Keys pressed during a slide are played once it ends, so
+// the same keys always give the same game however the presses line up with the ticks.
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct MoveQueue(pub VecDeque<Move>);
+
+// Number of gameplay ticks since the level started
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GameTick(pub u64);
+
+impl GameTick {
+    // Time played, in whole seconds
+    pub fn seconds(&self) -> u64 {
+        (self.0 as f64 / TICK_RATE) as u64
+    }
+}
+
+// How the level being played has gone so far
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LevelStats {
+    // Moves that went somewhere, blocked ones aren't counted
+    pub moves: u32,
+    // How many of those moves were teleports
+    pub teleports: u32,
+}
+
+// The board and stats from before each move, to step back through with undo, and those stepped
+// back from, to step forward through again with redo. A new move starts a new line of play, so
+// drops anything left to redo.
+#[derive(Resource, Debug, Default)]
+pub struct MoveHistory {
+    undo: Vec<(Board, LevelStats)>,
+    redo: Vec<(Board, LevelStats)>,
+}
+
+impl MoveHistory {
+    fn record(&mut self, board: Board, level_stats: LevelStats) {
+        self.undo.push((board, level_stats));
+        self.redo.clear();
+    }
+}
+
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                movement_controlls,
-                update_position,
-                check_wall,
-                check_potion,
+        app.init_resource::<Occupancy>()
+            .init_resource::<MoveQueue>()
+            .init_resource::<GameTick>()
+            .init_resource::<LevelStats>()
+            .init_resource::<MoveHistory>()
+            .insert_resource(Time::<Fixed>::from_hz(TICK_RATE))
+            .add_event::<CellEntered>()
+            .add_systems(OnEnter(GameState::GameSetup), reset_moves)
+            // An edited level starts again from the top, the history is of a different board
+            .add_systems(Update, reset_moves.run_if(on_event::<LevelReloaded>()))
+            .add_systems(
+                Update,
+                (
+                    // The player sits back while the solution is shown
+                    (queue_moves, undo_moves).run_if(not(resource_exists::<SolutionPlayback>())),
+                    update_sprites,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick,
+                    sync_occupancy,
+                    movement_controlls,
+                    update_position,
+                    check_potion,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
             )
-                .chain()
-                .run_if(in_state(GameState::GamePlay)),
-        )
-        .add_systems(OnEnter(PlayerState::Teleport), player_set_idle);
+            .add_systems(OnEnter(PlayerState::Teleport), player_set_idle);
     }
 }
 
+fn reset_moves(
+    mut move_queue: ResMut<MoveQueue>,
+    mut game_tick: ResMut<GameTick>,
+    mut level_stats: ResMut<LevelStats>,
+    mut history: ResMut<MoveHistory>,
+) {
+    move_queue.clear();
+    *game_tick = GameTick::default();
+    *level_stats = LevelStats::default();
+    *history = MoveHistory::default();
+}
+
+fn tick(mut game_tick: ResMut<GameTick>) {
+    game_tick.0 += 1;
+}
+
+fn queue_moves(input: Res<Input<KeyCode>>, mut move_queue: ResMut<MoveQueue>) {
+    for (key, mv) in [
+        (KeyCode::Up, Move::Slide(Direction::Up)),
+        (KeyCode::Down, Move::Slide(Direction::Down)),
+        (KeyCode::Left, Move::Slide(Direction::Left)),
+        (KeyCode::Right, Move::Slide(Direction::Right)),
+        (KeyCode::Space, Move::Teleport),
+    ] {
+        if input.just_released(key) {
+            move_queue.push_back(mv);
+        }
+    }
+}
+
+// Applies the next queued move to the board once the wizard is stopped. The board decides where
+// he stops and what he picks up, the sprites just catch up with it.
 fn movement_controlls(
     mut player_state: ResMut<NextState<PlayerState>>,
-    mut moveable_query: Query<(&mut Moveable, &mut Transform), Without<Teleporter>>,
-    mut teleporter_query: Query<&mut Transform, With<Teleporter>>,
-    input: Res<Input<KeyCode>>,
+    mut moveable_query: Query<(&mut Moveable, &mut GridPosition), Without<Teleporter>>,
+    mut teleporter_query: Query<&mut GridPosition, With<Teleporter>>,
+    mut move_queue: ResMut<MoveQueue>,
+    mut level_stats: ResMut<LevelStats>,
+    mut history: ResMut<MoveHistory>,
+    board: Option<ResMut<BoardState>>,
 ) {
-    let Ok((mut moveable, mut moveable_transform)) = moveable_query.get_single_mut() else {
+    let Ok((mut moveable, mut moveable_position)) = moveable_query.get_single_mut() else {
+        return;
+    };
+
+    let Ok(mut teleporter_position) = teleporter_query.get_single_mut() else {
         return;
     };
 
-    let Ok(mut teleporter_transform) = teleporter_query.get_single_mut() else {
+    let Some(mut board) = board else {
         return;
     };
 
     // Only allow new movement when player is stopped
-    if !matches!(moveable.direction, Direction::Stopped) {
+    if moveable.is_moving() {
         return;
     }
 
-    if input.just_released(KeyCode::Up) {
-        moveable.direction = Direction::Up;
-        moveable.speed = PLAYER_SPEED;
-        player_state.set(PlayerState::Moving);
-    }
-    if input.just_released(KeyCode::Down) {
-        moveable.direction = Direction::Down;
-        moveable.speed = PLAYER_SPEED;
-        player_state.set(PlayerState::Moving);
-    }
-    if input.just_released(KeyCode::Left) {
-        moveable.direction = Direction::Left;
-        moveable.speed = PLAYER_SPEED;
-        player_state.set(PlayerState::Moving);
+    let Some(mv) = move_queue.pop_front() else {
+        return;
+    };
+
+    let before = board.clone();
+    let outcome = board.apply_move(mv);
+    if !outcome.moved() {
+        return;
     }
-    if input.just_released(KeyCode::Right) {
-        moveable.direction = Direction::Right;
-        moveable.speed = PLAYER_SPEED;
-        player_state.set(PlayerState::Moving);
+    history.record(before, *level_stats);
+    level_stats.moves += 1;
+    if mv == Move::Teleport {
+        level_stats.teleports += 1;
     }
-    if input.just_released(KeyCode::Space) {
-        moveable.direction = Direction::Stopped;
-        moveable.speed = 0.0;
-        let teleporter_translation = teleporter_transform.translation;
-        let moveable_translation = moveable_transform.translation;
-
-        moveable_transform.translation = teleporter_translation;
-        teleporter_transform.translation = moveable_translation;
+    moveable_position.0 = outcome.to;
 
-        player_state.set(PlayerState::Teleport);
+    match (mv, outcome.teleporter) {
+        (Move::Slide(_), _) => {
+            moveable.path = outcome.path.into();
+            moveable.progress = 0.0;
+            player_state.set(PlayerState::Moving);
+        }
+        (Move::Teleport, Some(teleporter)) => {
+            moveable.cell = outcome.to;
+            teleporter_position.0 = teleporter;
+            player_state.set(PlayerState::Teleport);
+        }
+        (Move::Teleport, None) => (),
     }
 }
 
-fn update_position(mut player_query: Query<(&mut Transform, &Moveable)>, time: Res<Time>) {
-    let Ok((mut transform, moveable)) = player_query.get_single_mut() else {
+// Steps back (Z) or forward (Y) a move while the wizard is stopped, putting the wizard, teleporter
+// and potions back how the board has them
+fn undo_moves(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut history: ResMut<MoveHistory>,
+    mut move_queue: ResMut<MoveQueue>,
+    mut level_stats: ResMut<LevelStats>,
+    mut player_query: Query<(&mut Moveable, &mut GridPosition), Without<Teleporter>>,
+    mut teleporter_query: Query<&mut GridPosition, (With<Teleporter>, Without<Moveable>)>,
+    potion_query: Query<
+        (Entity, &GridPosition),
+        (With<Collectable>, Without<Moveable>, Without<Teleporter>),
+    >,
+    asset_map_query: Query<Entity, With<AssetMap>>,
+    ascii: Res<AsciiSheet>,
+    map_size: Res<MapSize>,
+    board: Option<ResMut<BoardState>>,
+) {
+    let (undo, redo) = (
+        keyboard_input.just_pressed(KeyCode::Z),
+        keyboard_input.just_pressed(KeyCode::Y),
+    );
+    if !undo && !redo {
+        return;
+    }
+
+    let (Ok((mut moveable, mut player_position)), Ok(mut teleporter_position), Some(mut board)) = (
+        player_query.get_single_mut(),
+        teleporter_query.get_single_mut(),
+        board,
+    ) else {
         return;
     };
 
-    if matches!(moveable.direction, Direction::Stopped) {
+    if moveable.is_moving() {
         return;
     }
 
-    let movement_amount = moveable.speed * time.delta_seconds();
+    let MoveHistory {
+        undo: back,
+        redo: forward,
+    } = &mut *history;
+    let (from, to) = if undo {
+        (back, forward)
+    } else {
+        (forward, back)
+    };
+    let Some((restored, restored_stats)) = from.pop() else {
+        return;
+    };
+    to.push((std::mem::replace(&mut board.0, restored), *level_stats));
+    *level_stats = restored_stats;
+
+    // Moves queued before the undo were meant for the board being left
+    move_queue.clear();
+
+    moveable.cell = board.player();
+    player_position.0 = board.player();
+    teleporter_position.0 = board.teleporter();
 
-    match moveable.direction {
-        Direction::Up => transform.translation.y += movement_amount,
-        Direction::Down => transform.translation.y -= movement_amount,
-        Direction::Left => transform.translation.x -= movement_amount,
-        Direction::Right => transform.translation.x += movement_amount,
-        _ => (),
+    // Potions picked up since come back, and those picked up again go
+    for (entity, position) in &potion_query {
+        if !board.has_potion(position.0) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    let Ok(asset_map) = asset_map_query.get_single() else {
+        return;
+    };
+    for cell in board.potions() {
+        if !potion_query.iter().any(|(_, position)| position.0 == cell) {
+            let potion = spawn_potion(&mut commands, &ascii, &map_size, cell);
+            commands.entity(asset_map).add_child(potion);
+        }
     }
 }
 
-fn check_potion(
-    mut commands: Commands,
-    mut player_query: Query<&mut Transform, (With<Moveable>, Without<Collectable>)>,
+fn update_position(
+    mut player_query: Query<&mut Moveable>,
     mut player_state: ResMut<NextState<PlayerState>>,
-    mut game_state: ResMut<NextState<GameState>>,
-    potion_query: Query<(Entity, &Transform), With<Collectable>>,
-    level: ResMut<Level>,
+    mut cell_entered: EventWriter<CellEntered>,
+    time: Res<Time<Fixed>>,
 ) {
-    let Ok(player_transform) = player_query.get_single_mut() else {
+    let Ok(mut moveable) = player_query.get_single_mut() else {
         return;
     };
 
-    for (potion_entity, potion_transform) in potion_query.iter() {
-        let hit = collide(
-            player_transform.translation,
-            Vec2::splat(TILE_SIZE),
-            potion_transform.translation,
-            Vec2::splat(TILE_SIZE),
-        )
-        .is_some();
-
-        if hit {
-            commands.entity(potion_entity).despawn_recursive();
-            player_state.set(PlayerState::CollectPotion);
-        }
+    if !moveable.is_moving() {
+        return;
     }
 
-    if potion_query.is_empty() {
-        if level.number == 10 {
-            println!("Game completed!");
-            game_state.set(GameState::GameCompleted);
-        } else {
-            println!("Level {} complete!", level.number);
-            game_state.set(GameState::GameSetup);
-        }
+    moveable.progress += PLAYER_SPEED / TILE_SIZE * time.delta_seconds();
+    while moveable.progress >= 1.0 {
+        let Some(next) = moveable.path.pop_front() else {
+            break;
+        };
+        moveable.cell = next;
+        moveable.progress -= 1.0;
+        cell_entered.send(CellEntered(next));
+    }
+
+    if !moveable.is_moving() {
+        moveable.progress = 0.0;
+        player_state.set(PlayerState::Idle);
+    }
+}
+
+// Draws the wizard part way between cells, and the teleporter wherever it now is
+fn update_sprites(
+    mut player_query: Query<(&mut Transform, &Moveable)>,
+    mut teleporter_query: Query<
+        (&mut Transform, &GridPosition),
+        (With<Teleporter>, Without<Moveable>, Changed<GridPosition>),
+    >,
+    map_size: Res<MapSize>,
+) {
+    for (mut transform, moveable) in &mut player_query {
+        let z = transform.translation.z;
+        let from = map_size.translation(moveable.cell.x, moveable.cell.y, z);
+        transform.translation = match moveable.path.front() {
+            Some(next) => from.lerp(map_size.translation(next.x, next.y, z), moveable.progress),
+            None => from,
+        };
+    }
+
+    for (mut transform, position) in &mut teleporter_query {
+        let (x, y) = (position.0.x, position.0.y);
+        transform.translation = map_size.translation(x, y, transform.translation.z);
     }
 }
 
-fn check_wall(
-    mut player_query: Query<(&mut Transform, &mut Moveable), Without<TileCollider>>,
+// Removes the potions the board says have been picked up as the wizard reaches them, and finishes
+// the level once he comes to a stop with none left
+fn check_potion(
+    mut commands: Commands,
+    mut cell_entered: EventReader<CellEntered>,
+    player_query: Query<&Moveable>,
     mut player_state: ResMut<NextState<PlayerState>>,
-    wall_query: Query<&Transform, With<TileCollider>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    potion_query: Query<(), With<Collectable>>,
+    occupancy: Res<Occupancy>,
+    board: Option<Res<BoardState>>,
 ) {
-    let Ok((mut player_transform, mut player_moveable)) = player_query.get_single_mut() else {
+    let Ok(moveable) = player_query.get_single() else {
+        return;
+    };
+
+    let Some(board) = board else {
         return;
     };
 
-    for wall_transform in wall_query.iter() {
-        if let Some(collision) = collide(
-            player_transform.translation,
-            Vec2::splat(TILE_SIZE),
-            wall_transform.translation,
-            Vec2::splat(TILE_SIZE),
-        ) {
-            // Moving left, collided with right side of wall
-            if matches!(player_moveable.direction, Direction::Left)
-                && matches!(collision, Collision::Right)
-            {
-                player_moveable.speed = 0.0;
-                player_moveable.direction = Direction::Stopped;
-                // Ensure we don't move in to the wall, as the collision may occur
-                // after we have moved 'into' it (as translation is a vec3 of f32s)
-                player_transform.translation.x = wall_transform.translation.x + TILE_SIZE;
-                player_state.set(PlayerState::Idle);
-            };
-
-            // Moving right, collided with left side of wall
-            if matches!(player_moveable.direction, Direction::Right)
-                && matches!(collision, Collision::Left)
-            {
-                player_moveable.speed = 0.0;
-                player_moveable.direction = Direction::Stopped;
-                // Ensure we don't move in to the wall, as the collision may occur
-                // after we have moved 'into' it (as translation is a vec3 of f32s)
-                player_transform.translation.x = wall_transform.translation.x - TILE_SIZE;
-                player_state.set(PlayerState::Idle);
-            };
-
-            // Moving up, collided with bottom side of wall
-            if matches!(player_moveable.direction, Direction::Up)
-                && matches!(collision, Collision::Bottom)
-            {
-                player_moveable.speed = 0.0;
-                player_moveable.direction = Direction::Stopped;
-                // Ensure we don't move in to the wall, as the collision may occur
-                // after we have moved 'into' it (as translation is a vec3 of f32s)
-                player_transform.translation.y = wall_transform.translation.y - TILE_SIZE;
-                player_state.set(PlayerState::Idle);
-            };
-
-            // Moving down, collided with top side of wall
-            if matches!(player_moveable.direction, Direction::Down)
-                && matches!(collision, Collision::Top)
-            {
-                player_moveable.speed = 0.0;
-                player_moveable.direction = Direction::Stopped;
-                // Ensure we don't move in to the wall, as the collision may occur
-                // after we have moved 'into' it (as translation is a vec3 of f32s)
-                player_transform.translation.y = wall_transform.translation.y + TILE_SIZE;
-                player_state.set(PlayerState::Idle);
-            };
+    for CellEntered(cell) in cell_entered.read() {
+        if board.has_potion(*cell) {
+            continue;
         }
+
+        for entity in occupancy.get(*cell) {
+            if potion_query.contains(*entity) {
+                commands.entity(*entity).despawn_recursive();
+                player_state.set(PlayerState::CollectPotion);
+            }
+        }
+    }
+
+    if board.is_won() && !moveable.is_moving() {
+        game_state.set(GameState::LevelCompleted);
     }
 }
 
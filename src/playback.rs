@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::Instant};
+
+use crate::levels::LevelReloaded;
+use crate::map::BoardState;
+use crate::movement::{MoveQueue, Moveable};
+use crate::sim::{Board, Move};
+use crate::solver::Solver;
+use crate::{despawn_screen, GameState, FRAME_BUDGET};
+
+// Pressing S plays the shortest solution from where the wizard is, for players who are stuck or
+// designers checking a level. The moves are fed through the move queue like key presses, so they
+// play out exactly as they would for the player, who can't move until the playback is stopped.
+// P pauses it, N steps a move at a time, and S again hands control back. The solution is searched
+// for a little each frame, and played once it is found.
+
+// Solved once on request, so it can afford a bigger search than a hint
+const PLAYBACK_MAX_STATES: usize = 1_000_000;
+
+// Wait between moves, so each one can be followed, teleports especially are instant
+const MOVE_DELAY: f32 = 0.4;
+
+// Present while the solution is being played
+#[derive(Resource, Debug)]
+pub struct SolutionPlayback {
+    // Moves still to play
+    moves: VecDeque<Move>,
+    total: usize,
+    paused: bool,
+    // Moves to play while paused, from stepping
+    steps: usize,
+    delay: Timer,
+}
+
+impl SolutionPlayback {
+    fn new(moves: Vec<Move>) -> Self {
+        Self {
+            total: moves.len(),
+            moves: moves.into(),
+            paused: false,
+            steps: 0,
+            delay: Timer::from_seconds(MOVE_DELAY, TimerMode::Once),
+        }
+    }
+
+    fn status(&self) -> String {
+        let played = self.total - self.moves.len();
+        if self.paused {
+            format!(
+                "Solution paused at move {played} of {}\nP: resume, N: step, S: stop",
+                self.total
+            )
+        } else {
+            format!(
+                "Showing the solution, move {played} of {}\nP: pause, S: stop",
+                self.total
+            )
+        }
+    }
+}
+
+// The solution being searched for, and the board it is for
+#[derive(Resource, Debug)]
+struct PendingSolution {
+    board: Board,
+    solver: Solver,
+}
+
+// Whether the solution was shown for any of the level being played, it doesn't count as solving
+// it yourself
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SolutionShown(pub bool);
+
+#[derive(Component, Debug)]
+struct OnPlayback;
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolutionShown>()
+            .add_systems(OnEnter(GameState::GameSetup), reset_solution_shown)
+            .add_systems(
+                Update,
+                (
+                    playback_controls,
+                    find_solution.run_if(resource_exists::<PendingSolution>()),
+                    play_solution,
+                    update_status,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
+            )
+            // An edited level is a different board to the one that was solved
+            .add_systems(Update, stop_playback.run_if(on_event::<LevelReloaded>()))
+            .add_systems(OnExit(GameState::GamePlay), stop_playback);
+    }
+}
+
+fn reset_solution_shown(mut solution_shown: ResMut<SolutionShown>) {
+    *solution_shown = SolutionShown::default();
+}
+
+fn stop_playback(mut commands: Commands, status_query: Query<Entity, With<OnPlayback>>) {
+    commands.remove_resource::<SolutionPlayback>();
+    commands.remove_resource::<PendingSolution>();
+    despawn_screen(status_query, commands);
+}
+
+fn playback_controls(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    playback: Option<ResMut<SolutionPlayback>>,
+    pending: Option<Res<PendingSolution>>,
+    player_query: Query<&Moveable>,
+    status_query: Query<Entity, With<OnPlayback>>,
+    board: Option<Res<BoardState>>,
+) {
+    if let Some(mut playback) = playback {
+        if keyboard_input.just_pressed(KeyCode::S) {
+            println!("Solution stopped");
+            stop_playback(commands, status_query);
+        } else if keyboard_input.just_pressed(KeyCode::P) {
+            playback.paused = !playback.paused;
+            playback.steps = 0;
+        } else if keyboard_input.just_pressed(KeyCode::N) {
+            playback.paused = true;
+            playback.steps += 1;
+        }
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::S) {
+        return;
+    }
+
+    if pending.is_some() {
+        println!("Solution stopped");
+        stop_playback(commands, status_query);
+        return;
+    }
+
+    let (Ok(moveable), Some(board)) = (player_query.get_single(), board) else {
+        return;
+    };
+    if moveable.is_moving() || board.is_won() {
+        return;
+    }
+
+    commands.insert_resource(PendingSolution {
+        board: board.0.clone(),
+        solver: Solver::new(&board, PLAYBACK_MAX_STATES),
+    });
+    spawn_status(&mut commands);
+}
+
+fn find_solution(
+    mut commands: Commands,
+    mut pending: ResMut<PendingSolution>,
+    mut solution_shown: ResMut<SolutionShown>,
+    mut move_queue: ResMut<MoveQueue>,
+    status_query: Query<Entity, With<OnPlayback>>,
+    board: Option<Res<BoardState>>,
+) {
+    // Moving while it is being searched for leaves it solving the wrong board
+    if !board.is_some_and(|board| board.0 == pending.board) {
+        stop_playback(commands, status_query);
+        return;
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(result) = pending.solver.step() else {
+            continue;
+        };
+
+        let solution = match result {
+            Ok(solution) => solution,
+            Err(err) => {
+                println!("No solution to show: {err}");
+                stop_playback(commands, status_query);
+                return;
+            }
+        };
+        println!(
+            "Showing solution: {}",
+            solution
+                .moves
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect::<String>()
+        );
+
+        // Moves the player queued were for a game they are no longer playing
+        move_queue.clear();
+        solution_shown.0 = true;
+        commands.remove_resource::<PendingSolution>();
+        commands.insert_resource(SolutionPlayback::new(solution.moves));
+        return;
+    }
+}
+
+// Queues the next move of the solution once the last one has finished and the delay has passed
+fn play_solution(
+    playback: Option<ResMut<SolutionPlayback>>,
+    mut move_queue: ResMut<MoveQueue>,
+    player_query: Query<&Moveable>,
+    time: Res<Time>,
+) {
+    let (Some(mut playback), Ok(moveable)) = (playback, player_query.get_single()) else {
+        return;
+    };
+
+    if moveable.is_moving() || !move_queue.is_empty() || playback.moves.is_empty() {
+        return;
+    }
+    if playback.paused && playback.steps == 0 {
+        return;
+    }
+
+    playback.delay.tick(time.delta());
+    if !playback.delay.finished() {
+        return;
+    }
+
+    let Some(mv) = playback.moves.pop_front() else {
+        return;
+    };
+    move_queue.push_back(mv);
+    playback.delay.reset();
+    if playback.paused {
+        playback.steps -= 1;
+    }
+}
+
+fn spawn_status(commands: &mut Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Vw(100.0),
+                    top: Val::Px(60.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnPlayback,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::YELLOW,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                })
+                .with_text_alignment(TextAlignment::Center)
+                .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+            );
+        });
+}
+
+fn update_status(
+    playback: Option<Res<SolutionPlayback>>,
+    pending: Option<Res<PendingSolution>>,
+    status_query: Query<&Children, With<OnPlayback>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let status = match (playback, pending) {
+        (Some(playback), _) => playback.status(),
+        (None, Some(_)) => "Finding the solution...\nS: stop".into(),
+        (None, None) => return,
+    };
+    for children in &status_query {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != status {
+                text.sections[0].value = status.clone();
+            }
+        }
+    }
+}
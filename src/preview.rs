@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::ascii::TILE_SIZE;
+use crate::map::{BoardState, MapSize};
+use crate::movement::{MoveQueue, Moveable};
+use crate::sim::{Cell, Direction, Move};
+use crate::GameState;
+
+// An assist, off unless turned on in the settings. While the wizard is stopped, faint ghosts show
+// where each arrow key would slide him and the potions he would pick up on the way, and where a
+// teleport would put him. They come from applying each move to a copy of the board, so always
+// agree with what the move really does.
+
+const SLIDE_GHOST: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+const POTION_GHOST: Color = Color::rgba(1.0, 1.0, 0.0, 0.3);
+const TELEPORT_GHOST: Color = Color::rgba(0.3, 0.8, 1.0, 0.3);
+
+// Whether the ghosts are shown, also used to mark the buttons for it on the settings screen
+#[derive(Resource, Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GhostPreview(pub bool);
+
+pub struct PreviewPlugin;
+
+impl Plugin for PreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostPreview>().add_systems(
+            Update,
+            draw_ghosts.run_if(
+                in_state(GameState::GamePlay).and_then(resource_equals(GhostPreview(true))),
+            ),
+        );
+    }
+}
+
+fn draw_ghosts(
+    mut gizmos: Gizmos,
+    player_query: Query<&Moveable>,
+    move_queue: Res<MoveQueue>,
+    map_size: Option<Res<MapSize>>,
+    board: Option<Res<BoardState>>,
+) {
+    let (Ok(moveable), Some(map_size), Some(board)) = (player_query.get_single(), map_size, board)
+    else {
+        return;
+    };
+
+    // Ghosts for a board that is about to change would only flicker
+    if moveable.is_moving() || !move_queue.is_empty() || board.is_won() {
+        return;
+    }
+
+    let centre = |cell: Cell| map_size.translation(cell.x, cell.y, 0.0).truncate();
+    let player = centre(board.player());
+
+    for direction in Direction::ALL {
+        let outcome = board.clone().apply_move(Move::Slide(direction));
+        if !outcome.moved() {
+            continue;
+        }
+
+        let to = centre(outcome.to);
+        gizmos.line_2d(player, to, SLIDE_GHOST);
+        gizmos.circle_2d(to, TILE_SIZE * 0.3, SLIDE_GHOST);
+        for cell in outcome.collected {
+            gizmos.rect_2d(
+                centre(cell),
+                0.0,
+                Vec2::splat(TILE_SIZE * 0.8),
+                POTION_GHOST,
+            );
+        }
+    }
+
+    let outcome = board.clone().apply_move(Move::Teleport);
+    if outcome.moved() {
+        gizmos.circle_2d(centre(outcome.to), TILE_SIZE * 0.3, TELEPORT_GHOST);
+    }
+}
@@ -0,0 +1,43 @@
+// A small seeded random number generator (SplitMix64). The same seed always gives the same
+// numbers, on every platform, so anything generated from a seed can be shared and replayed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in `0..bound`, `bound` must not be zero
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    // A number in the given range, which must not be empty
+    pub fn range(&mut self, range: std::ops::RangeInclusive<usize>) -> usize {
+        range.start() + self.below(range.end() - range.start() + 1)
+    }
+
+    // True with the given probability, from 0 to 1
+    pub fn chance(&mut self, probability: f32) -> bool {
+        // The top 24 bits, as that is all the precision an f32 has
+        ((self.next_u64() >> 40) as f32 / (1u64 << 24) as f32) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.below(items.len()))
+    }
+}
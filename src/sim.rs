@@ -0,0 +1,338 @@
+use std::fmt;
+
+use crate::level_def::{LevelDef, LevelRules, Tile};
+
+// The rules of the game, kept free of Bevy so they can be tested and reused by tools. A `Board` is
+// the state of a level part way through being played, and moves are applied to it one at a time:
+//
+// - the wizard slides in a straight line until the next tile is a wall or the teleporter
+// - every potion on the way, including the tile he stops on, is picked up
+// - teleporting swaps the wizard and the teleporter (unless the level's rules forbid it)
+// - the level is won once the last potion has been picked up
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    // The neighbouring cell in the given direction, `None` when that would be off the top or left
+    pub fn step(self, direction: Direction) -> Option<Cell> {
+        let (x, y) = match direction {
+            Direction::Up => (Some(self.x), self.y.checked_sub(1)),
+            Direction::Down => (Some(self.x), self.y.checked_add(1)),
+            Direction::Left => (self.x.checked_sub(1), Some(self.y)),
+            Direction::Right => (self.x.checked_add(1), Some(self.y)),
+        };
+        Some(Cell::new(x?, y?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Slide(Direction),
+    Teleport,
+}
+
+// Moves are written as a single letter, U(p), D(own), L(eft), R(ight) or T(eleport)
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            Move::Slide(Direction::Up) => 'U',
+            Move::Slide(Direction::Down) => 'D',
+            Move::Slide(Direction::Left) => 'L',
+            Move::Slide(Direction::Right) => 'R',
+            Move::Teleport => 'T',
+        };
+        write!(f, "{letter}")
+    }
+}
+
+// What happened when a move was applied, a blocked move leaves the board untouched and the player
+// where they started
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub from: Cell,
+    pub to: Cell,
+    // Cells passed through when sliding, in order and ending with `to`
+    pub path: Vec<Cell>,
+    // Where the teleporter ended up, when the player swapped places with it
+    pub teleporter: Option<Cell>,
+    // Potions picked up, in the order they were reached
+    pub collected: Vec<Cell>,
+    // Whether that was the last potion
+    pub won: bool,
+}
+
+impl MoveOutcome {
+    fn blocked(at: Cell) -> Self {
+        Self {
+            from: at,
+            to: at,
+            path: Vec::new(),
+            teleporter: None,
+            collected: Vec::new(),
+            won: false,
+        }
+    }
+
+    pub fn moved(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    rules: LevelRules,
+    // Row major, like `LevelDef::tiles`
+    walls: Vec<bool>,
+    potions: Vec<bool>,
+    potions_left: usize,
+    player: Cell,
+    teleporter: Cell,
+}
+
+impl Board {
+    pub fn new(level: &LevelDef) -> Self {
+        let find = |wanted| {
+            let idx = level.tiles.iter().position(|tile| *tile == wanted);
+            idx.map_or(Cell::new(0, 0), |idx| {
+                Cell::new(idx % level.width, idx / level.width)
+            })
+        };
+        let potions: Vec<bool> = level.tiles.iter().map(|t| *t == Tile::Potion).collect();
+
+        Self {
+            width: level.width,
+            height: level.height,
+            rules: level.rules,
+            walls: level.tiles.iter().map(|t| *t == Tile::Wall).collect(),
+            potions_left: potions.iter().filter(|potion| **potion).count(),
+            potions,
+            player: find(Tile::Player),
+            teleporter: find(Tile::Teleporter),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn rules(&self) -> LevelRules {
+        self.rules
+    }
+
+    pub fn player(&self) -> Cell {
+        self.player
+    }
+
+    pub fn teleporter(&self) -> Cell {
+        self.teleporter
+    }
+
+    fn index(&self, cell: Cell) -> Option<usize> {
+        (cell.x < self.width && cell.y < self.height).then_some(cell.y * self.width + cell.x)
+    }
+
+    // Anything off the board counts as wall
+    pub fn is_wall(&self, cell: Cell) -> bool {
+        self.index(cell).is_none_or(|idx| self.walls[idx])
+    }
+
+    pub fn has_potion(&self, cell: Cell) -> bool {
+        self.index(cell).is_some_and(|idx| self.potions[idx])
+    }
+
+    // Potions still to be picked up
+    pub fn potions(&self) -> impl Iterator<Item = Cell> + '_ {
+        self.potions
+            .iter()
+            .enumerate()
+            .filter(|(_, potion)| **potion)
+            .map(|(idx, _)| Cell::new(idx % self.width, idx / self.width))
+    }
+
+    pub fn potions_left(&self) -> usize {
+        self.potions_left
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.potions_left == 0
+    }
+
+    // Whether the player would stop before entering this cell
+    pub fn is_blocked(&self, cell: Cell) -> bool {
+        self.is_wall(cell) || cell == self.teleporter
+    }
+
+    // The cells the player passes through sliding from `from`, ending with the one they stop on.
+    // Empty when the way is blocked straight away.
+    pub fn slide_path(&self, from: Cell, direction: Direction) -> Vec<Cell> {
+        let mut path = Vec::new();
+        let mut cell = from;
+        while let Some(next) = cell.step(direction).filter(|next| !self.is_blocked(*next)) {
+            path.push(next);
+            cell = next;
+        }
+        path
+    }
+
+    pub fn apply_move(&mut self, mv: Move) -> MoveOutcome {
+        let from = self.player;
+        match mv {
+            Move::Slide(direction) => {
+                let path = self.slide_path(from, direction);
+                let Some(to) = path.last().copied() else {
+                    return MoveOutcome::blocked(from);
+                };
+
+                let mut collected = Vec::new();
+                for &cell in &path {
+                    if let Some(idx) = self.index(cell).filter(|idx| self.potions[*idx]) {
+                        self.potions[idx] = false;
+                        self.potions_left -= 1;
+                        collected.push(cell);
+                    }
+                }
+                self.player = to;
+
+                MoveOutcome {
+                    from,
+                    to,
+                    path,
+                    teleporter: None,
+                    won: !collected.is_empty() && self.is_won(),
+                    collected,
+                }
+            }
+            Move::Teleport => {
+                if !self.rules.teleport {
+                    return MoveOutcome::blocked(from);
+                }
+                std::mem::swap(&mut self.player, &mut self.teleporter);
+
+                MoveOutcome {
+                    from,
+                    to: self.player,
+                    path: Vec::new(),
+                    teleporter: Some(self.teleporter),
+                    collected: Vec::new(),
+                    won: false,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level_def::parse_level;
+
+    fn board(source: &str) -> Board {
+        Board::new(&parse_level(source).expect("test levels are valid"))
+    }
+
+    #[test]
+    fn slides_stop_at_walls() {
+        let mut board = board("#######\n#p..#o#\n#t....#\n#######\n");
+        let outcome = board.apply_move(Move::Slide(Direction::Right));
+        assert!(outcome.moved());
+        assert_eq!(outcome.to, Cell::new(3, 1));
+        assert_eq!(outcome.path, vec![Cell::new(2, 1), Cell::new(3, 1)]);
+        assert_eq!(board.player(), Cell::new(3, 1));
+    }
+
+    #[test]
+    fn slides_stop_before_the_teleporter() {
+        let mut board = board("#######\n#p..to#\n#######\n");
+        let outcome = board.apply_move(Move::Slide(Direction::Right));
+        assert_eq!(outcome.to, Cell::new(3, 1));
+    }
+
+    #[test]
+    fn slides_collect_every_potion_on_the_way() {
+        let mut board = board("#######\n#po.oo#\n#t...o#\n#######\n");
+        let outcome = board.apply_move(Move::Slide(Direction::Right));
+        assert_eq!(
+            outcome.collected,
+            vec![Cell::new(2, 1), Cell::new(4, 1), Cell::new(5, 1)]
+        );
+        assert_eq!(board.potions_left(), 1);
+        assert!(!board.has_potion(Cell::new(2, 1)));
+        assert!(board.has_potion(Cell::new(5, 2)));
+        assert!(!outcome.won);
+    }
+
+    #[test]
+    fn teleporting_swaps_the_player_and_teleporter() {
+        let mut board = board("#####\n#p.o#\n#..t#\n#####\n");
+        let outcome = board.apply_move(Move::Teleport);
+        assert!(outcome.moved());
+        assert_eq!(outcome.to, Cell::new(3, 2));
+        assert_eq!(outcome.teleporter, Some(Cell::new(1, 1)));
+        assert_eq!(board.player(), Cell::new(3, 2));
+        assert_eq!(board.teleporter(), Cell::new(1, 1));
+    }
+
+    #[test]
+    fn blocked_moves_change_nothing() {
+        let mut board = board("rules: no-teleport\n---\n#####\n#p.o#\n#t..#\n#####\n");
+        let before = board.clone();
+        for mv in [
+            Move::Slide(Direction::Up),
+            Move::Slide(Direction::Left),
+            // The teleporter is in the way
+            Move::Slide(Direction::Down),
+            Move::Teleport,
+        ] {
+            let outcome = board.apply_move(mv);
+            assert!(!outcome.moved(), "{mv}");
+            assert_eq!(outcome.to, outcome.from, "{mv}");
+            assert_eq!(board, before, "{mv}");
+        }
+    }
+
+    #[test]
+    fn picking_up_the_last_potion_wins() {
+        let mut board = board("######\n#p.o.#\n#o..t#\n######\n");
+        assert!(!board.is_won());
+
+        let outcome = board.apply_move(Move::Slide(Direction::Right));
+        assert!(!outcome.won);
+        assert!(!board.is_won());
+
+        board.apply_move(Move::Slide(Direction::Left));
+        let outcome = board.apply_move(Move::Slide(Direction::Down));
+        assert!(outcome.won);
+        assert!(board.is_won());
+    }
+}
@@ -0,0 +1,351 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use crate::sim::{Board, Cell, Direction, Move};
+
+// Finds the shortest solution to a board, or proves there isn't one, with an A* search over
+// (wizard cell, teleporter cell, potions left). Moves follow the same rules as `sim::Board`.
+//
+// The heuristic is the fewest rows and columns that between them hold every potion left: a slide
+// only ever travels along one row or one column, so at least that many moves are still needed.
+// It is worked out as a maximum matching of rows to columns (König's theorem) and cached per set
+// of potions, as many states share the same potions.
+
+// Enough for anything hand made, while stopping runaway searches on huge generated boards
+pub const MAX_STATES: usize = 5_000_000;
+
+// Potions are tracked as bits of a `u64`
+pub const MAX_POTIONS: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub moves: Vec<Move>,
+    pub states_explored: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    // Every reachable state was searched without collecting all of the potions
+    Unsolvable { states_explored: usize },
+    // The search gave up before finding a solution or running out of states
+    LimitReached { states_explored: usize },
+    TooManyPotions(usize),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable { states_explored } => {
+                write!(f, "level is unsolvable ({states_explored} states explored)")
+            }
+            SolveError::LimitReached { states_explored } => {
+                write!(f, "gave up after exploring {states_explored} states")
+            }
+            SolveError::TooManyPotions(count) => write!(
+                f,
+                "level has {count} potions, the solver handles at most {MAX_POTIONS}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+pub fn solve(board: &Board) -> Result<Solution, SolveError> {
+    solve_with_limit(board, MAX_STATES)
+}
+
+pub fn solve_with_limit(board: &Board, max_states: usize) -> Result<Solution, SolveError> {
+    Solver::new(board, max_states).finish()
+}
+
+// Solves a board a state at a time, so the work can be spread over several frames
+#[derive(Debug)]
+pub struct Solver {
+    search: Result<Search, SolveError>,
+}
+
+impl Solver {
+    pub fn new(board: &Board, max_states: usize) -> Self {
+        Self {
+            search: Grid::new(board).map(|grid| Search::new(grid, max_states)),
+        }
+    }
+
+    // Explores one more state, `None` while the search is still going
+    pub fn step(&mut self) -> Option<Result<Solution, SolveError>> {
+        match &mut self.search {
+            Ok(search) => search.step(),
+            Err(err) => Some(Err(err.clone())),
+        }
+    }
+
+    // Keeps searching until there is a solution or the search gives up
+    pub fn finish(&mut self) -> Result<Solution, SolveError> {
+        loop {
+            if let Some(result) = self.step() {
+                return result;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct State {
+    player: u32,
+    teleporter: u32,
+    potions: u64,
+}
+
+// A slide from one cell, ignoring the teleporter
+#[derive(Debug, Default)]
+struct Slide {
+    cells: Vec<u32>,
+    // Potions passed by the time each cell is reached
+    potions: Vec<u64>,
+}
+
+#[derive(Debug)]
+struct Grid {
+    teleport: bool,
+    start: State,
+    // Slides from every cell in each of `Direction::ALL`, indexed by `cell * 4 + direction`
+    slides: Vec<Slide>,
+    // Potion bit to cell
+    potion_cells: Vec<Cell>,
+}
+
+impl Grid {
+    fn new(board: &Board) -> Result<Self, SolveError> {
+        let potion_cells: Vec<Cell> = board.potions().collect();
+        if potion_cells.len() > MAX_POTIONS {
+            return Err(SolveError::TooManyPotions(potion_cells.len()));
+        }
+
+        let width = board.width();
+        let index = |cell: Cell| (cell.y * width + cell.x) as u32;
+        let mut potion_bits = vec![0u64; width * board.height()];
+        for (bit, cell) in potion_cells.iter().enumerate() {
+            potion_bits[index(*cell) as usize] = 1 << bit;
+        }
+
+        let mut slides = Vec::with_capacity(width * board.height() * 4);
+        for y in 0..board.height() {
+            for x in 0..width {
+                for direction in Direction::ALL {
+                    let mut slide = Slide::default();
+                    let mut passed = 0;
+                    let mut cell = Cell::new(x, y);
+                    if !board.is_wall(cell) {
+                        while let Some(next) = cell.step(direction).filter(|c| !board.is_wall(*c)) {
+                            passed |= potion_bits[index(next) as usize];
+                            slide.cells.push(index(next));
+                            slide.potions.push(passed);
+                            cell = next;
+                        }
+                    }
+                    slides.push(slide);
+                }
+            }
+        }
+
+        Ok(Self {
+            teleport: board.rules().teleport,
+            start: State {
+                player: index(board.player()),
+                teleporter: index(board.teleporter()),
+                potions: potion_bits.iter().fold(0, |all, bit| all | bit),
+            },
+            slides,
+            potion_cells,
+        })
+    }
+
+    // The state each move leads to, skipping moves that go nowhere
+    fn moves(&self, state: State) -> impl Iterator<Item = (Move, State)> + '_ {
+        let slides = Direction::ALL
+            .iter()
+            .enumerate()
+            .filter_map(move |(idx, direction)| {
+                let slide = &self.slides[state.player as usize * 4 + idx];
+                // Stop short of the teleporter when it is in the way
+                let len = slide
+                    .cells
+                    .iter()
+                    .position(|cell| *cell == state.teleporter)
+                    .unwrap_or(slide.cells.len());
+                let last = len.checked_sub(1)?;
+
+                let next = State {
+                    player: slide.cells[last],
+                    potions: state.potions & !slide.potions[last],
+                    ..state
+                };
+                Some((Move::Slide(*direction), next))
+            });
+
+        let teleport = self.teleport.then_some((
+            Move::Teleport,
+            State {
+                player: state.teleporter,
+                teleporter: state.player,
+                ..state
+            },
+        ));
+
+        slides.chain(teleport)
+    }
+
+    // `lines_needed`, cached per set of potions
+    fn heuristic(&self, heuristics: &mut HashMap<u64, u32>, potions: u64) -> u32 {
+        *heuristics
+            .entry(potions)
+            .or_insert_with(|| self.lines_needed(potions))
+    }
+
+    // Fewest rows and columns covering the potions, i.e the size of a maximum matching between
+    // the rows and columns that hold potions
+    fn lines_needed(&self, potions: u64) -> u32 {
+        let cells: Vec<Cell> = (0..self.potion_cells.len())
+            .filter(|bit| potions & (1 << bit) != 0)
+            .map(|bit| self.potion_cells[bit])
+            .collect();
+
+        let mut column_match: HashMap<usize, usize> = HashMap::new();
+        let mut rows: Vec<usize> = cells.iter().map(|cell| cell.y).collect();
+        rows.sort_unstable();
+        rows.dedup();
+
+        let mut matched = 0;
+        for row in rows {
+            let mut visited = Vec::new();
+            if augment(row, &cells, &mut column_match, &mut visited) {
+                matched += 1;
+            }
+        }
+        matched
+    }
+
+    fn path_to(
+        &self,
+        mut state: State,
+        visited: &HashMap<State, (u32, Option<(State, Move)>)>,
+    ) -> Vec<Move> {
+        let mut moves = Vec::new();
+        while let Some((_, Some((previous, mv)))) = visited.get(&state) {
+            moves.push(*mv);
+            state = *previous;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+// Where an A* search over a grid has got to
+#[derive(Debug)]
+struct Search {
+    grid: Grid,
+    max_states: usize,
+    // Heuristic for each set of potions left
+    heuristics: HashMap<u64, u32>,
+    // Each state's move count and the state and move it was reached from
+    visited: HashMap<State, (u32, Option<(State, Move)>)>,
+    // Ordered by estimated total moves, then by most moves so far to head for a goal
+    open: BinaryHeap<Reverse<(u32, Reverse<u32>, State)>>,
+    states_explored: usize,
+}
+
+impl Search {
+    fn new(grid: Grid, max_states: usize) -> Self {
+        let start = grid.start;
+        let mut search = Self {
+            grid,
+            max_states,
+            heuristics: HashMap::new(),
+            visited: HashMap::from([(start, (0, None))]),
+            open: BinaryHeap::new(),
+            states_explored: 0,
+        };
+        let estimate = search.grid.heuristic(&mut search.heuristics, start.potions);
+        search.open.push(Reverse((estimate, Reverse(0), start)));
+        search
+    }
+
+    fn step(&mut self) -> Option<Result<Solution, SolveError>> {
+        let states_explored = self.states_explored;
+        let Some(Reverse((_, Reverse(moves), state))) = self.open.pop() else {
+            return Some(Err(SolveError::Unsolvable { states_explored }));
+        };
+
+        // Skip entries for states since reached in fewer moves
+        if self
+            .visited
+            .get(&state)
+            .is_some_and(|(best, _)| *best < moves)
+        {
+            return None;
+        }
+
+        if state.potions == 0 {
+            return Some(Ok(Solution {
+                moves: self.grid.path_to(state, &self.visited),
+                states_explored,
+            }));
+        }
+
+        self.states_explored += 1;
+        if self.states_explored > self.max_states {
+            return Some(Err(SolveError::LimitReached {
+                states_explored: self.states_explored,
+            }));
+        }
+
+        let Self {
+            grid,
+            heuristics,
+            visited,
+            open,
+            ..
+        } = self;
+        for (mv, next) in grid.moves(state) {
+            let next_moves = moves + 1;
+            if visited
+                .get(&next)
+                .is_some_and(|(best, _)| *best <= next_moves)
+            {
+                continue;
+            }
+            visited.insert(next, (next_moves, Some((state, mv))));
+            let estimate = next_moves + grid.heuristic(heuristics, next.potions);
+            open.push(Reverse((estimate, Reverse(next_moves), next)));
+        }
+        None
+    }
+}
+
+// Kuhn's augmenting path step, tries to match `row` with a column, moving other rows along as
+// needed
+fn augment(
+    row: usize,
+    cells: &[Cell],
+    column_match: &mut HashMap<usize, usize>,
+    visited: &mut Vec<usize>,
+) -> bool {
+    for cell in cells.iter().filter(|cell| cell.y == row) {
+        if visited.contains(&cell.x) {
+            continue;
+        }
+        visited.push(cell.x);
+
+        let free = match column_match.get(&cell.x) {
+            None => true,
+            Some(&other) => augment(other, cells, column_match, visited),
+        };
+        if free {
+            column_match.insert(cell.x, row);
+            return true;
+        }
+    }
+    false
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use crate::levels::{LevelManager, LevelPack};
+
 use super::{despawn_screen, GameState};
 
 // This plugin will display a splash screen for 10 seconds before switch to the menu
@@ -54,13 +56,17 @@ fn splash_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(SplashTimer(Timer::from_seconds(3.0, TimerMode::Once)));
 }
 
-// Tick the timer, and change state when finished
+// Tick the timer, and change state when finished and the levels are ready to play
 fn countdown(
     mut game_state: ResMut<NextState<GameState>>,
     time: Res<Time>,
     mut timer: ResMut<SplashTimer>,
+    asset_server: Res<AssetServer>,
+    mut level_manager: ResMut<LevelManager>,
+    mut pack_assets: ResMut<Assets<LevelPack>>,
 ) {
-    if timer.tick(time.delta()).finished() {
+    if timer.tick(time.delta()).finished() && level_manager.is_loaded(&asset_server) {
+        level_manager.drop_failed_levels(&asset_server, &mut pack_assets);
         game_state.set(GameState::Menu);
     }
 }
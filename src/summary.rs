@@ -0,0 +1,292 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, Instant},
+};
+
+use crate::button::{button_style, button_system, button_text_style, NORMAL_BUTTON};
+use crate::game::game_levels_next;
+use crate::hint::HintsUsed;
+use crate::levels::Levels;
+use crate::movement::{GameTick, LevelStats};
+use crate::playback::SolutionShown;
+use crate::sim::Board;
+use crate::solver::Solver;
+use crate::{despawn_screen, GameState, Level, FRAME_BUDGET};
+
+// How each level went is shown once it is finished, scored against its par: the fewest moves it
+// can be done in. Par comes from the level's metadata, or the solver for levels without one. The
+// solver is given a little time each frame, so the par shows up once the level is being played.
+
+// Enough for any level that is fun to play, anything bigger goes without a par
+const PAR_MAX_STATES: usize = 200_000;
+
+// Par of the level being played, when known
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LevelPar(pub Option<u32>);
+
+// Pars found by the solver, kept by the board they were solved for so restarting a level doesn't
+// solve it again while a level edited on disk still is
+#[derive(Resource, Debug, Default)]
+struct SolvedPars(HashMap<Board, Option<u32>>);
+
+// The level being played while its par is being solved
+#[derive(Resource, Debug)]
+struct PendingPar {
+    board: Board,
+    solver: Solver,
+}
+
+// Stars for finishing a level in a number of moves: three for par, two for up to half as many
+// again, and one for finishing at all
+pub fn stars(moves: u32, par: u32) -> u32 {
+    if moves <= par {
+        3
+    } else if moves * 2 <= par * 3 {
+        2
+    } else {
+        1
+    }
+}
+
+// Time written as minutes and seconds, e.g 1:05
+pub fn format_time(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[derive(Component, Debug)]
+struct OnLevelSummary;
+
+#[derive(Component, Debug, Default)]
+struct NextLevelButton {
+    // Moving on was asked for before the next level was there
+    waiting: bool,
+}
+
+pub struct SummaryPlugin;
+
+impl Plugin for SummaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelPar>()
+            .init_resource::<SolvedPars>()
+            .add_systems(OnEnter(GameState::GameSetup), find_par)
+            .add_systems(Update, solve_par.run_if(resource_exists::<PendingPar>()))
+            .add_systems(OnEnter(GameState::LevelCompleted), level_summary)
+            .add_systems(
+                Update,
+                (next_level, button_system).run_if(in_state(GameState::LevelCompleted)),
+            )
+            .add_systems(
+                OnExit(GameState::LevelCompleted),
+                despawn_screen::<OnLevelSummary>,
+            );
+    }
+}
+
+fn find_par(
+    mut commands: Commands,
+    mut level_par: ResMut<LevelPar>,
+    solved: Res<SolvedPars>,
+    pending: Option<Res<PendingPar>>,
+    level: Res<Level>,
+    levels: Levels,
+) {
+    level_par.0 = None;
+    let Some(level_def) = levels.get(&level) else {
+        commands.remove_resource::<PendingPar>();
+        return;
+    };
+
+    if level_def.par.is_some() {
+        level_par.0 = level_def.par;
+        commands.remove_resource::<PendingPar>();
+        return;
+    }
+
+    let board = Board::new(level_def);
+    if let Some(par) = solved.0.get(&board) {
+        level_par.0 = *par;
+        commands.remove_resource::<PendingPar>();
+    } else if !pending.is_some_and(|pending| pending.board == board) {
+        // Restarting while it is still being solved carries on with the same search
+        commands.insert_resource(PendingPar {
+            solver: Solver::new(&board, PAR_MAX_STATES),
+            board,
+        });
+    }
+}
+
+fn solve_par(
+    mut commands: Commands,
+    mut pending: ResMut<PendingPar>,
+    mut solved: ResMut<SolvedPars>,
+    mut level_par: ResMut<LevelPar>,
+) {
+    let started = Instant::now();
+    while started.elapsed() < FRAME_BUDGET {
+        let Some(result) = pending.solver.step() else {
+            continue;
+        };
+
+        let par = result.ok().map(|solution| solution.moves.len() as u32);
+        solved.0.insert(pending.board.clone(), par);
+        level_par.0 = par;
+        commands.remove_resource::<PendingPar>();
+        return;
+    }
+}
+
+fn level_summary(
+    mut commands: Commands,
+    level: Res<Level>,
+    levels: Levels,
+    level_stats: Res<LevelStats>,
+    level_par: Res<LevelPar>,
+    game_tick: Res<GameTick>,
+    hints_used: Res<HintsUsed>,
+    solution_shown: Res<SolutionShown>,
+) {
+    let title = match levels.get(&level) {
+        Some(level_def) if !level_def.name.is_empty() => format!("{} complete!", level_def.name),
+        _ => format!("Level {} complete!", level.number()),
+    };
+
+    let mut lines = vec![
+        match level_par.0 {
+            Some(par) => format!("Moves: {} (par {par})", level_stats.moves),
+            None => format!("Moves: {}", level_stats.moves),
+        },
+        format!("Teleports: {}", level_stats.teleports),
+        format!("Time: {}", format_time(game_tick.seconds())),
+        format!("Hints used: {}", hints_used.0),
+    ];
+    if solution_shown.0 {
+        lines.push("No stars, the solution was shown".into());
+    } else if let Some(par) = level_par.0 {
+        let stars = stars(level_stats.moves, par);
+        // The default font has no star, so they are drawn with asterisks
+        lines.push(format!(
+            "{}{} {stars} of 3 stars",
+            "*".repeat(stars as usize),
+            "-".repeat(3 - stars as usize)
+        ));
+    }
+
+    let button_text = if levels.is_last(&level) {
+        "Finish"
+    } else {
+        "Next level"
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.8)),
+                ..default()
+            },
+            OnLevelSummary,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            title,
+                            TextStyle {
+                                font_size: 40.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+                    for line in lines {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                line,
+                                TextStyle {
+                                    font_size: 20.0,
+                                    color: Color::WHITE,
+                                    ..default()
+                                },
+                            )
+                            .with_style(Style {
+                                margin: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            }),
+                        );
+                    }
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            NextLevelButton::default(),
+                        ))
+                        .with_children(|parent| {
+                            parent
+                                .spawn(TextBundle::from_section(button_text, button_text_style()));
+                        });
+                });
+        });
+}
+
+// Moves on when the button is clicked, or Enter or Space is pressed. The next level of an endless
+// run can still be being generated, in which case it moves on once the level is ready.
+fn next_level(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<NextLevelButton>)>,
+    mut button_query: Query<(&mut NextLevelButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut level: ResMut<Level>,
+    levels: Levels,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Ok((mut button, children)) = button_query.get_single_mut() else {
+        return;
+    };
+
+    let pressed = interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if pressed || keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        button.waiting = true;
+    }
+    if !button.waiting {
+        return;
+    }
+
+    let next = Level {
+        index: level.index + 1,
+    };
+    if !levels.is_last(&level) && levels.get(&next).is_none() {
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != "Generating..." {
+                text.sections[0].value = "Generating...".into();
+            }
+        }
+        return;
+    }
+
+    game_levels_next(&mut level, &levels, &mut game_state);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::clipboard;
+
+// A single line text field, the typed value is shown by the `Text` on the same entity
+#[derive(Component, Debug)]
+pub struct TextInput {
+    pub value: String,
+    // Characters that can be typed or pasted, anything else is ignored
+    allowed: fn(char) -> bool,
+}
+
+impl TextInput {
+    pub fn new(allowed: fn(char) -> bool) -> Self {
+        Self {
+            value: String::new(),
+            allowed,
+        }
+    }
+}
+
+// Common style for all text input text
+pub fn text_input_style() -> TextStyle {
+    TextStyle {
+        font_size: 20.0,
+        color: Color::WHITE,
+        ..default()
+    }
+}
+
+// This system handles typing, deleting and pasting (Ctrl+V) into all text inputs
+pub fn text_input_system(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut input_query: Query<(&mut TextInput, &mut Text)>,
+) {
+    let mut typed: String = characters.read().map(|event| event.char).collect();
+
+    let ctrl = keyboard_input.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    if ctrl && keyboard_input.just_pressed(KeyCode::V) {
+        typed.extend(clipboard::paste());
+    }
+
+    for (mut input, mut text) in &mut input_query {
+        if keyboard_input.just_pressed(KeyCode::Back) {
+            input.value.pop();
+        }
+        let allowed = input.allowed;
+        input.value.extend(typed.chars().filter(|c| allowed(*c)));
+
+        // Only touch the text when it changes, so it isn't laid out again every frame
+        let display = format!("{}_", input.value);
+        if text.sections[0].value != display {
+            text.sections[0].value = display;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use image::{imageops, RgbaImage};
+
+use crate::ascii::{SHEET_COLUMNS, TILE_SIZE};
+use crate::level_def::{LevelDef, Tile};
+use crate::map::{FLOOR_SPRITE, PLAYER_SPRITE, POTION_SPRITE, TELEPORTER_SPRITE, WALL_SPRITE};
+
+// Draws a level with the game's sprite sheet entirely on the CPU, so it works without a GPU (or a
+// window). Tiles are laid out the same as `spawn_map` and `spawn_assets` lay them out: a wall or
+// floor sprite for every tile, with potions, the player and the teleporter drawn over the floor.
+// The result is `scale` times the size of the board in game pixels.
+pub fn render(level: &LevelDef, sheet: &RgbaImage, scale: u32) -> RgbaImage {
+    let tile_size = TILE_SIZE as u32;
+    let mut image = RgbaImage::new(
+        level.width as u32 * tile_size,
+        level.height as u32 * tile_size,
+    );
+
+    for y in 0..level.height {
+        for x in 0..level.width {
+            let tile = level.tile(x, y);
+            let base = match tile {
+                Tile::Wall => WALL_SPRITE,
+                _ => FLOOR_SPRITE,
+            };
+            let item = match tile {
+                Tile::Potion => Some(POTION_SPRITE),
+                Tile::Player => Some(PLAYER_SPRITE),
+                Tile::Teleporter => Some(TELEPORTER_SPRITE),
+                _ => None,
+            };
+
+            let (left, top) = ((x as u32 * tile_size) as i64, (y as u32 * tile_size) as i64);
+            for index in std::iter::once(base).chain(item) {
+                imageops::overlay(&mut image, &sprite(sheet, index), left, top);
+            }
+        }
+    }
+
+    if scale > 1 {
+        let (width, height) = image.dimensions();
+        image = imageops::resize(
+            &image,
+            width * scale,
+            height * scale,
+            imageops::FilterType::Nearest,
+        );
+    }
+
+    image
+}
+
+fn sprite(sheet: &RgbaImage, index: usize) -> RgbaImage {
+    let tile_size = TILE_SIZE as u32;
+    let column = (index % SHEET_COLUMNS) as u32;
+    let row = (index / SHEET_COLUMNS) as u32;
+
+    imageops::crop_imm(
+        sheet,
+        column * tile_size,
+        row * tile_size,
+        tile_size,
+        tile_size,
+    )
+    .to_image()
+}
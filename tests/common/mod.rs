@@ -0,0 +1,32 @@
+// Helpers shared by the tests, not every test uses all of them
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use slidey::level_def::{parse_level, LevelDef};
+use slidey::level_files::{builtin_packs, read_levels, LevelFile};
+use slidey::sim::Board;
+
+pub fn level(source: &str) -> LevelDef {
+    parse_level(source).unwrap_or_else(|errors| panic!("{errors:?}"))
+}
+
+pub fn board(source: &str) -> Board {
+    Board::new(&level(source))
+}
+
+// Every level in the packs shipped with the game
+pub fn shipped_levels() -> Vec<LevelFile> {
+    let assets = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets");
+    let packs = builtin_packs(&assets);
+    assert!(!packs.is_empty(), "no built in packs");
+
+    packs
+        .iter()
+        .flat_map(|pack| {
+            let levels = read_levels(pack).unwrap_or_else(|err| panic!("{err}"));
+            assert!(!levels.is_empty(), "{} has no levels", pack.display());
+            levels
+        })
+        .collect()
+}
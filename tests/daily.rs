@@ -0,0 +1,66 @@
+mod common;
+
+use slidey::daily::{self, parse_results, DailyResult, Date};
+use slidey::sim::Board;
+use slidey::solver::solve;
+
+fn date(year: i32, month: u32, day: u32) -> Date {
+    Date { year, month, day }
+}
+
+#[test]
+fn days_since_1970_convert_to_dates() {
+    for (days, expected) in [
+        (0, date(1970, 1, 1)),
+        (-1, date(1969, 12, 31)),
+        (11_016, date(2000, 2, 29)),
+        (20_744, date(2026, 10, 18)),
+        (47_541, date(2100, 3, 1)),
+    ] {
+        assert_eq!(Date::from_days(days), expected, "{days} days");
+    }
+}
+
+#[test]
+fn daily_puzzle_is_the_same_for_everyone_and_solvable_in_par() {
+    let today = date(2026, 10, 18);
+    let level = daily::level(today).expect("daily puzzle");
+    assert_eq!(
+        daily::level(today).map(|again| again.tiles),
+        Some(level.tiles.clone())
+    );
+    assert_ne!(
+        daily::level(date(2026, 10, 19)).map(|next| next.tiles),
+        Some(level.tiles.clone())
+    );
+
+    let reparsed = common::level(&level.to_string());
+    let solution = solve(&Board::new(&reparsed)).unwrap_or_else(|err| panic!("{err}"));
+    assert_eq!(reparsed.par, Some(solution.moves.len() as u32));
+}
+
+#[test]
+fn results_round_trip_through_storage() {
+    let results = vec![
+        DailyResult {
+            date: date(2026, 10, 17),
+            moves: 21,
+            par: 19,
+            seconds: 95,
+        },
+        DailyResult {
+            date: date(2026, 10, 18),
+            moves: 16,
+            par: 16,
+            seconds: 42,
+        },
+    ];
+    let source: String = results.iter().map(|result| format!("{result}\n")).collect();
+
+    // Anything that isn't a result is skipped
+    assert_eq!(parse_results(&format!("{source}not a result\n")), results);
+    assert_eq!(
+        results[0].summary(),
+        "Wizards Conundrum daily 2026-10-17\nSolved in 21 moves (par 19) in 1:35"
+    );
+}
@@ -0,0 +1,53 @@
+use slidey::endless::{DepthGenerator, EndlessRun};
+use slidey::generator::{Difficulty, GeneratorOptions};
+use slidey::level_def::LevelDef;
+use slidey::rng::Rng;
+
+fn level(run: &EndlessRun, depth: usize) -> LevelDef {
+    let options = EndlessRun::options(depth);
+    let mut generator = run.generator(depth);
+    // Every attempt in the band, then every attempt in the widened one
+    for _ in 0..=options.attempts * 2 {
+        if let Some(result) = generator.step() {
+            return result.unwrap_or_else(|err| panic!("{err}"));
+        }
+    }
+    panic!("depth {depth} was still being generated");
+}
+
+#[test]
+fn runs_with_the_same_seed_play_the_same_levels() {
+    for depth in [1, 2, 10] {
+        let first = level(&EndlessRun::new(42), depth);
+        assert_eq!(first.name, format!("Depth {depth}"));
+        assert_eq!(first, level(&EndlessRun::new(42), depth));
+    }
+}
+
+#[test]
+fn depths_with_nothing_in_their_band_widen_it_and_still_get_a_level() {
+    // Too few attempts to find a level this long, but enough for one half as long
+    let options = GeneratorOptions {
+        difficulty: Difficulty {
+            moves: 16..=16,
+            branching: 0.0..=5.0,
+        },
+        attempts: 30,
+        ..EndlessRun::options(1)
+    };
+    let generate = || {
+        let mut generator = DepthGenerator::new(1, options.clone(), Rng::new(0));
+        for _ in 0..=options.attempts * 2 {
+            if let Some(result) = generator.step() {
+                assert!(generator.is_widened());
+                return result.unwrap_or_else(|err| panic!("{err}"));
+            }
+        }
+        panic!("depth 1 was still being generated");
+    };
+
+    let level = generate();
+    assert_eq!(level.name, "Depth 1");
+    assert!((8..=16).contains(&level.par.unwrap()));
+    assert_eq!(level, generate());
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use slidey::generator::{generate, Difficulty, GeneratorOptions};
+use slidey::rng::Rng;
+use slidey::sim::Board;
+
+#[test]
+fn generated_levels_are_solvable_in_par() {
+    let mut rng = Rng::new(18);
+    for difficulty in [Difficulty::EASY, Difficulty::MEDIUM, Difficulty::HARD] {
+        let options = GeneratorOptions {
+            difficulty: difficulty.clone(),
+            ..Default::default()
+        };
+        let generated = generate(&options, &mut rng).unwrap_or_else(|err| panic!("{err}"));
+        let level = common::level(&generated.level.to_string());
+
+        let moves = generated.solution.len();
+        assert_eq!(level.par, Some(moves as u32));
+        assert!(difficulty.moves.contains(&moves), "{moves} moves");
+        assert!(difficulty.branching.contains(&generated.branching));
+
+        let mut board = Board::new(&level);
+        for mv in &generated.solution {
+            board.apply_move(*mv);
+        }
+        assert!(board.is_won());
+    }
+}
+
+#[test]
+fn hard_levels_have_fewer_moves_to_choose_from_than_easy_ones() {
+    let mean_branching = |difficulty: Difficulty| {
+        let options = GeneratorOptions {
+            difficulty,
+            ..Default::default()
+        };
+        let mut rng = Rng::new(7);
+        let levels = 12;
+        let total: f32 = (0..levels)
+            .map(|_| {
+                generate(&options, &mut rng)
+                    .unwrap_or_else(|err| panic!("{err}"))
+                    .branching
+            })
+            .sum();
+        total / levels as f32
+    };
+
+    // Long solutions thread through the board, with fewer ways to go at each step
+    let easy = mean_branching(Difficulty::EASY);
+    let hard = mean_branching(Difficulty::HARD);
+    assert!(easy - hard > 0.1, "easy {easy}, hard {hard}");
+}
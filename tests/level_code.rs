@@ -0,0 +1,74 @@
+mod common;
+
+use common::{level, shipped_levels};
+use slidey::level_code::{decode, encode, LevelCodeError};
+use slidey::level_def::{LevelDef, LevelRules, Tile};
+
+const CELLAR: &str = "name: The Cellar
+author: Paul Cockrell
+hint: Slide through a bottle to pick it up
+par: 3
+rules: no-teleport
+---
+############
+#..p..t.o..#
+#.#......o.#
+############
+";
+
+fn cellar() -> LevelDef {
+    level(CELLAR)
+}
+
+#[test]
+fn levels_round_trip_through_codes() {
+    assert_eq!(decode(&encode(&cellar())), Ok(cellar()));
+
+    for file in shipped_levels() {
+        assert_eq!(
+            decode(&encode(&file.level)),
+            Ok(file.level),
+            "{}",
+            file.path.display()
+        );
+    }
+}
+
+#[test]
+fn changed_codes_fail_the_checksum() {
+    let code = encode(&cellar());
+    let middle = code.len() / 2;
+    let changed = if &code[middle..=middle] == "A" {
+        "B"
+    } else {
+        "A"
+    };
+    let code = format!("{}{changed}{}", &code[..middle], &code[middle + 1..]);
+    assert_eq!(decode(&code), Err(LevelCodeError::Checksum));
+}
+
+#[test]
+fn cut_short_codes_are_rejected() {
+    let code = encode(&cellar());
+    // Short enough to have no checksum left, or one that no longer matches
+    assert_eq!(decode(&code[..2]), Err(LevelCodeError::Truncated));
+    assert!(decode(&code[..code.len() - 4]).is_err());
+    assert_eq!(decode(""), Err(LevelCodeError::Truncated));
+}
+
+#[test]
+fn codes_claiming_huge_levels_are_rejected_before_making_room_for_them() {
+    // Too thin to stand in, however long
+    let thin = LevelDef {
+        name: String::new(),
+        author: None,
+        hint: None,
+        par: None,
+        music: None,
+        rules: LevelRules::default(),
+        width: 1 << 40,
+        height: 2,
+        tiles: vec![Tile::Wall],
+    };
+    assert_eq!(decode(&encode(&thin)), Err(LevelCodeError::Malformed));
+}
@@ -0,0 +1,101 @@
+use slidey::level_def::{parse_level, LevelError, LevelErrorKind};
+
+fn errors(source: &str) -> Vec<LevelError> {
+    match parse_level(source) {
+        Ok(level) => panic!("parsed a broken level: {level:?}"),
+        Err(errors) => errors,
+    }
+}
+
+fn error(line: usize, column: usize, kind: LevelErrorKind) -> LevelError {
+    LevelError { line, column, kind }
+}
+
+#[test]
+fn short_rows_are_reported_where_they_end() {
+    assert_eq!(
+        errors("#####\n#pto#\n####\n"),
+        vec![error(
+            3,
+            5,
+            LevelErrorKind::WrongWidth {
+                expected: 5,
+                found: 4
+            }
+        )]
+    );
+}
+
+#[test]
+fn missing_player_and_teleporter_are_reported_at_the_map() {
+    assert_eq!(
+        errors("#####\n#.to#\n#####\n"),
+        vec![error(1, 1, LevelErrorKind::MissingPlayer)]
+    );
+    assert_eq!(
+        errors("#####\n#p.o#\n#####\n"),
+        vec![error(1, 1, LevelErrorKind::MissingTeleporter)]
+    );
+}
+
+#[test]
+fn duplicate_player_and_teleporter_are_reported_at_the_second() {
+    assert_eq!(
+        errors("######\n#ptop#\n######\n"),
+        vec![error(2, 5, LevelErrorKind::DuplicatePlayer)]
+    );
+    assert_eq!(
+        errors("######\n#ptot#\n######\n"),
+        vec![error(2, 5, LevelErrorKind::DuplicateTeleporter)]
+    );
+}
+
+#[test]
+fn unknown_characters_are_reported_where_they_are() {
+    assert_eq!(
+        errors("######\n#pt?o#\n######\n"),
+        vec![error(2, 4, LevelErrorKind::UnknownCharacter('?'))]
+    );
+}
+
+#[test]
+fn gaps_in_the_border_are_reported_where_they_are() {
+    assert_eq!(
+        errors("#####\n.pto#\n#####\n"),
+        vec![error(2, 1, LevelErrorKind::OpenBorder)]
+    );
+}
+
+#[test]
+fn levels_without_potions_are_reported_at_the_map_after_the_header() {
+    assert_eq!(
+        errors("name: Empty\n---\n####\n#pt#\n####\n"),
+        vec![error(3, 1, LevelErrorKind::NoPotions)]
+    );
+}
+
+#[test]
+fn sizes_bigger_than_the_map_are_reported_at_the_header() {
+    assert_eq!(
+        errors("size: 99999999999x99999999999\n---\n#####\n#pto#\n#####\n"),
+        vec![error(
+            1,
+            7,
+            LevelErrorKind::WrongHeight {
+                expected: 99999999999,
+                found: 3
+            }
+        )]
+    );
+    assert_eq!(
+        errors("size: 99999999999x3\n---\n#####\n#pto#\n#####\n"),
+        vec![error(
+            1,
+            7,
+            LevelErrorKind::WrongWidth {
+                expected: 99999999999,
+                found: 5
+            }
+        )]
+    );
+}
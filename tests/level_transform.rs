@@ -0,0 +1,54 @@
+mod common;
+
+use slidey::level_def::{LevelDef, Tile};
+use slidey::level_transform::LevelTransform;
+
+// Not square, and with no symmetry, so every transform gives a different map
+const LEVEL: &str = "#######
+#p..o.#
+#.#..o#
+#o..t.#
+#######
+";
+
+fn count(level: &LevelDef, tile: Tile) -> usize {
+    level.tiles.iter().filter(|found| **found == tile).count()
+}
+
+#[test]
+fn transforms_keep_every_tile_and_undo() {
+    let level = common::level(LEVEL);
+
+    for transform in LevelTransform::ALL {
+        let transformed = transform.apply(&level);
+        if transform.swaps_axes() {
+            assert_eq!(
+                (transformed.width, transformed.height),
+                (5, 7),
+                "{transform}"
+            );
+        } else {
+            assert_eq!(
+                (transformed.width, transformed.height),
+                (7, 5),
+                "{transform}"
+            );
+        }
+        for tile in [Tile::Player, Tile::Teleporter, Tile::Potion, Tile::Wall] {
+            assert_eq!(
+                count(&transformed, tile),
+                count(&level, tile),
+                "{transform} {tile:?}"
+            );
+        }
+        if transform != LevelTransform::Identity {
+            assert_ne!(transformed.tiles, level.tiles, "{transform}");
+        }
+
+        assert_eq!(
+            transform.inverse().apply(&transformed),
+            level,
+            "{transform}"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::shipped_levels;
+use slidey::level_def::parse_level;
+use slidey::level_files::LevelFile;
+use slidey::sim::Board;
+use slidey::solver::solve;
+
+#[test]
+fn every_level_round_trips_through_the_file_format() {
+    for LevelFile { path, level } in shipped_levels() {
+        let reparsed = parse_level(&level.to_string())
+            .unwrap_or_else(|errors| panic!("{}: {errors:?}", path.display()));
+        assert_eq!(reparsed, level, "{}", path.display());
+    }
+}
+
+#[test]
+fn every_level_is_solvable_in_par() {
+    for LevelFile { path, level } in shipped_levels() {
+        let solution =
+            solve(&Board::new(&level)).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+
+        // Play the solution through, so the solver and the game can't disagree about the rules
+        let mut board = Board::new(&level);
+        for mv in &solution.moves {
+            assert!(
+                board.apply_move(*mv).moved(),
+                "{}: {mv} is blocked",
+                path.display()
+            );
+        }
+        assert!(
+            board.is_won(),
+            "{}: solution leaves potions",
+            path.display()
+        );
+
+        assert_eq!(
+            level.par,
+            Some(solution.moves.len() as u32),
+            "{}: par should be the shortest solution",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use std::collections::{HashSet, VecDeque};
+
+use common::{board, shipped_levels};
+use slidey::sim::{Board, Direction, Move};
+use slidey::solver::{solve, solve_with_limit, SolveError, Solver};
+
+// Fewest moves to win found by trying every move from every board reached, nothing clever
+fn brute_force(start: &Board) -> Option<usize> {
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    while let Some((board, moves)) = queue.pop_front() {
+        if board.is_won() {
+            return Some(moves);
+        }
+        for mv in Direction::ALL
+            .map(Move::Slide)
+            .into_iter()
+            .chain([Move::Teleport])
+        {
+            let mut next = board.clone();
+            if next.apply_move(mv).moved() && seen.insert(next.clone()) {
+                queue.push_back((next, moves + 1));
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn solutions_are_as_short_as_brute_force_finds() {
+    let mut boards = vec![
+        board("#######\n#p..o.#\n#.#..o#\n#o..t.#\n#######\n"),
+        board("######\n#p.o.#\n#o..t#\n######\n"),
+        board("rules: no-teleport\n---\n######\n#p..o#\n#o.t.#\n######\n"),
+    ];
+    boards.extend(shipped_levels().iter().map(|file| Board::new(&file.level)));
+
+    for start in boards {
+        let solution = solve(&start).unwrap_or_else(|err| panic!("{err}"));
+        assert_eq!(Some(solution.moves.len()), brute_force(&start), "{start:?}");
+
+        let mut board = start.clone();
+        for mv in &solution.moves {
+            assert!(board.apply_move(*mv).moved(), "{start:?}");
+        }
+        assert!(board.is_won(), "{start:?}");
+    }
+}
+
+#[test]
+fn unreachable_potions_are_unsolvable() {
+    let walled_in = board("#######\n#p.t#o#\n#######\n");
+    assert!(matches!(
+        solve(&walled_in),
+        Err(SolveError::Unsolvable { .. })
+    ));
+}
+
+#[test]
+fn searches_give_up_at_the_limit() {
+    let start = board("#######\n#p..o.#\n#.#..o#\n#o..t.#\n#######\n");
+    assert!(matches!(
+        solve_with_limit(&start, 1),
+        Err(SolveError::LimitReached { .. })
+    ));
+    assert!(solve_with_limit(&start, 10_000).is_ok());
+}
+
+#[test]
+fn stepping_finds_the_same_solution() {
+    let start = board("#######\n#p..o.#\n#.#..o#\n#o..t.#\n#######\n");
+    let mut solver = Solver::new(&start, 10_000);
+    let mut steps = 0;
+    let stepped = loop {
+        steps += 1;
+        if let Some(result) = solver.step() {
+            break result;
+        }
+    };
+    assert!(steps > 1);
+    assert_eq!(stepped, solve_with_limit(&start, 10_000));
+}
@@ -0,0 +1,21 @@
+use slidey::summary::{format_time, stars};
+
+#[test]
+fn stars_are_awarded_against_par() {
+    for (moves, par, expected) in [
+        (10, 12, 3),
+        (12, 12, 3),
+        (13, 12, 2),
+        (18, 12, 2),
+        (19, 12, 1),
+    ] {
+        assert_eq!(stars(moves, par), expected, "{moves} moves, par {par}");
+    }
+}
+
+#[test]
+fn times_show_minutes_and_seconds() {
+    assert_eq!(format_time(0), "0:00");
+    assert_eq!(format_time(65), "1:05");
+    assert_eq!(format_time(3_600), "60:00");
+}